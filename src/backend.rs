@@ -0,0 +1,165 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! Walking a `Document` for output formats other than SVG.
+//!
+//! `draw` flattens all `World` transforms and hands absolute geometry to a
+//! `Backend`, the same way the SVG output would place it.
+
+use crate::{Color, Point, Document};
+use crate::curve;
+use crate::drawable;
+use crate::element::Element;
+use crate::graphic::{Area, Axis, Circle, Line, Path, World};
+use crate::matrix3::Matrix3;
+use crate::svg;
+use crate::text::{DEFAULT_FONT_SIZE, Label};
+use crate::tree::{self, Tree};
+
+/// Receives the geometry of a document in backend coordinates.
+///
+/// Widths, radii, sizes and dash patterns are already scaled.
+pub trait Backend {
+    /// A closed polygon; the first ring is the outline, the others are holes
+    /// wound the other way, so the polygon can be filled with the nonzero rule.
+    fn polygon(&mut self, rings: &[Vec<(f64, f64)>], stroke: Color, fill: Color, stroke_width: f64);
+
+    /// An open polyline, `dash` is empty for a solid line.
+    fn polyline(&mut self, points: &[(f64, f64)], stroke: Color, stroke_width: f64, dash: &[f64]);
+
+    fn circle(&mut self, center: (f64, f64), r: f64, stroke: Color, fill: Color, stroke_width: f64);
+
+    /// Text centered on `position`, which is on the baseline.
+    fn text(&mut self, position: (f64, f64), size: f64, text: &str);
+
+    /// Start of the contents of a world, including its axis.
+    fn begin_world(&mut self, _name: &str) {}
+
+    fn end_world(&mut self) {}
+}
+
+/// Draw `document` on `backend`; `matrix` maps plan coordinates to backend coordinates.
+///
+/// `Element::Custom` drawables are skipped.
+pub fn draw<B: Backend>(document: &Document, matrix: &Matrix3, backend: &mut B) {
+    let world = World::new("Document", document.origin);
+    let mut walker = Walker { backend };
+    walker.backend.begin_world(&world.name);
+    walker.axis(&world, matrix, svg::STROKE_WIDTH);
+    for child in document.children() {
+        walker.element(child, matrix, svg::STROKE_WIDTH);
+    }
+    walker.backend.end_world();
+}
+
+/// Parse an SVG `stroke-dasharray` value.
+pub fn dash_pattern(dash: &str) -> Vec<f64> {
+    let mut pattern: Vec<f64> = dash.split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|s| s.parse().ok())
+        .filter(|d: &f64| *d >= 0.0)
+        .collect();
+    if pattern.len() % 2 == 1 {
+        pattern.extend(pattern.clone());
+    }
+    pattern
+}
+
+/// Twice the signed area, positive for counterclockwise rings.
+fn signed_area(ring: &[(f64, f64)]) -> f64 {
+    (0..ring.len()).map(|i| {
+        let ((x0, y0), (x1, y1)) = (ring[i], ring[(i + 1) % ring.len()]);
+        x0 * y1 - x1 * y0
+    }).sum()
+}
+
+fn point(c: &Point, matrix: &Matrix3) -> (f64, f64) {
+    c.transform(matrix).tup()
+}
+
+struct Walker<'a, B: Backend> {
+    backend: &'a mut B,
+}
+
+impl<'a, B: Backend> Walker<'a, B> {
+    fn element(&mut self, element: &Element, matrix: &Matrix3, stroke_width: f64) {
+        match element {
+            Element::Path(x) => self.path(x, matrix, stroke_width),
+            Element::Area(x) => self.area(x, matrix, stroke_width),
+            Element::Circle(x) => self.circle(x, matrix, stroke_width),
+            Element::Line(x) => self.line(x, matrix, stroke_width),
+            Element::Label(x) => self.label(x, matrix),
+            Element::Tree(x) => self.tree(x, matrix),
+            Element::World(x) => self.world(x, matrix, stroke_width),
+            Element::Custom(_) => (),
+        }
+    }
+
+    fn path(&mut self, path: &Path, matrix: &Matrix3, stroke_width: f64) {
+        let scale = drawable::scale_factor(matrix);
+        let points: Vec<_> = path.flatten(curve::TOLERANCE).iter().map(|p| point(p, matrix)).collect();
+        let dash: Vec<f64> = path.stroke_dash.as_ref()
+            .map(|d| dash_pattern(d).iter().map(|d| d * scale).collect())
+            .unwrap_or_default();
+        let width = path.stroke_width.unwrap_or(stroke_width) * scale;
+        self.backend.polyline(&points, path.color, width, &dash);
+    }
+
+    fn area(&mut self, area: &Area, matrix: &Matrix3, stroke_width: f64) {
+        let mut rings: Vec<Vec<(f64, f64)>> = area.rings(curve::TOLERANCE).iter()
+            .map(|r| r.iter().map(|c| point(c, matrix)).collect())
+            .collect();
+        // the outline counterclockwise, the holes clockwise
+        for (i, ring) in rings.iter_mut().enumerate() {
+            if (signed_area(ring) < 0.0) == (i == 0) {
+                ring.reverse();
+            }
+        }
+        let width = area.stroke_width.unwrap_or(stroke_width) * drawable::scale_factor(matrix);
+        self.backend.polygon(&rings, area.color, area.fill, width);
+        if let Some(world) = &area.world {
+            self.world(world, matrix, stroke_width);
+        }
+    }
+
+    fn circle(&mut self, c: &Circle, matrix: &Matrix3, stroke_width: f64) {
+        let scale = drawable::scale_factor(matrix);
+        let center = point(&Point::new(c.cx, c.cy), matrix);
+        self.backend.circle(center, c.r * scale, c.color, c.fill, stroke_width * scale);
+    }
+
+    fn line(&mut self, line: &Line, matrix: &Matrix3, stroke_width: f64) {
+        let width = line.stroke_width.unwrap_or(stroke_width) * drawable::scale_factor(matrix);
+        self.backend.polyline(&[point(&line.p1, matrix), point(&line.p2, matrix)], line.color, width, &[]);
+    }
+
+    fn label(&mut self, label: &Label, matrix: &Matrix3) {
+        let size = label.size.unwrap_or(DEFAULT_FONT_SIZE) * drawable::scale_factor(matrix);
+        self.backend.text(point(&label.location, matrix), size, &label.text);
+    }
+
+    fn tree(&mut self, tree: &Tree, matrix: &Matrix3) {
+        self.label(&Label::new(tree.label_location, &tree.name), matrix);
+        if let Some(d) = tree.crown_diameter {
+            let crown = Circle::new(tree.location.x, tree.location.y, d / 2.0, Color::Green, Color::DarkGreen);
+            self.circle(&crown, matrix, tree::STROKE_WIDTH);
+        }
+        let trunk = Circle::new(tree.location.x, tree.location.y, tree.trunk_diameter / 2.0, Color::Brown, Color::Maroon);
+        self.circle(&trunk, matrix, tree::STROKE_WIDTH);
+    }
+
+    fn world(&mut self, world: &World, matrix: &Matrix3, stroke_width: f64) {
+        let matrix = world.matrix() * *matrix;
+        let stroke_width = world.stroke_width.unwrap_or(stroke_width);
+        self.backend.begin_world(&world.name);
+        self.axis(world, &matrix, stroke_width);
+        for element in &world.elements {
+            self.element(element, &matrix, stroke_width);
+        }
+        self.backend.end_world();
+    }
+
+    fn axis(&mut self, world: &World, matrix: &Matrix3, stroke_width: f64) {
+        for line in world.axis().iter().flat_map(Axis::lines) {
+            self.line(&line, matrix, stroke_width);
+        }
+    }
+}
@@ -0,0 +1,395 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! Boolean operations on polygons with holes.
+//!
+//! A polygon is a list of rings, see `polygon`: the outline followed by its
+//! holes. The edges of both operands are split where they cross, every
+//! piece is kept or dropped depending on which side of the other operand it
+//! is on, and the kept pieces are linked into new rings.
+
+use std::collections::HashMap;
+
+use crate::Point;
+use crate::polygon;
+use crate::vector2::Vector2;
+
+/// Distance under which points are considered the same.
+const EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Everything covered by either polygon.
+    Union,
+    /// Everything covered by both polygons.
+    Intersection,
+    /// Everything covered by the first polygon but not by the second.
+    Difference,
+    /// Everything covered by exactly one of the polygons.
+    Xor,
+}
+
+/// Where a piece of an edge of one polygon lies relative to the other polygon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Side {
+    Inside,
+    Outside,
+    /// On an edge of the other polygon going the same way.
+    Same,
+    /// On an edge of the other polygon going the other way.
+    Opposite,
+}
+
+/// The corners of all polygons, so equal points get the same index.
+struct Vertices(Vec<Point>);
+
+impl Vertices {
+    fn index(&mut self, c: Point) -> usize {
+        let found = self.0.iter().position(|v| (v.x - c.x).hypot(v.y - c.y) < EPSILON);
+        found.unwrap_or_else(|| {
+            self.0.push(c);
+            self.0.len() - 1
+        })
+    }
+}
+
+fn edges(rings: &[Vec<Point>]) -> Vec<(Point, Point)> {
+    rings.iter().flat_map(|r| (0..r.len()).map(move |i| (r[i], r[(i + 1) % r.len()]))).collect()
+}
+
+/// The rings of `polygon` without degenerate ones, the outline
+/// counterclockwise and the holes clockwise.
+fn normalize(rings: &[Vec<Point>]) -> Vec<Vec<Point>> {
+    rings.iter().enumerate().filter_map(|(i, ring)| {
+        let area = polygon::signed_area(ring);
+        if area.abs() < EPSILON {
+            return None;
+        }
+        let mut ring = ring.clone();
+        if (area < 0.0) == (i == 0) {
+            ring.reverse();
+        }
+        Some(ring)
+    }).collect()
+}
+
+/// Add the points where edge `a` and edge `b` meet to their splits, as
+/// positions along the edges.
+fn intersect(a: (Point, Point), b: (Point, Point),
+             a_splits: &mut Vec<(f64, Point)>, b_splits: &mut Vec<(f64, Point)>) {
+    let (p, q) = (Vector2::from(&a.0), Vector2::from(&b.0));
+    let (r, s) = (Vector2::from(&a.1) - p, Vector2::from(&b.1) - q);
+    let (r_len, s_len) = (r.length(), s.length());
+    if r_len < EPSILON || s_len < EPSILON {
+        return;
+    }
+    let denominator = r.perp_dot_product(s);
+    if denominator.abs() > EPSILON * r_len * s_len {
+        let t = (q - p).perp_dot_product(s) / denominator;
+        let u = (q - p).perp_dot_product(r) / denominator;
+        let (te, ue) = (EPSILON / r_len, EPSILON / s_len);
+        if t < -te || t > 1.0 + te || u < -ue || u > 1.0 + ue {
+            return;
+        }
+        // prefer existing corners over computed points
+        let x = if t < te {
+            a.0
+        } else if t > 1.0 - te {
+            a.1
+        } else if u < ue {
+            b.0
+        } else if u > 1.0 - ue {
+            b.1
+        } else {
+            let x = p + r * t;
+            Point::new(x.x, x.y)
+        };
+        a_splits.push((t, x));
+        b_splits.push((u, x));
+    } else if (q - p).perp_dot_product(r).abs() < EPSILON * r_len {
+        // collinear: split each edge at the ends of the other
+        for &c in &[b.0, b.1] {
+            let t = (Vector2::from(&c) - p).dot_product(r) / (r_len * r_len);
+            if t > 0.0 && t < 1.0 {
+                a_splits.push((t, c));
+            }
+        }
+        for &c in &[a.0, a.1] {
+            let u = (Vector2::from(&c) - q).dot_product(s) / (s_len * s_len);
+            if u > 0.0 && u < 1.0 {
+                b_splits.push((u, c));
+            }
+        }
+    }
+}
+
+/// The edges split at all crossings, as pairs of vertex indices.
+fn split(edges: &[(Point, Point)], splits: &mut [Vec<(f64, Point)>], vertices: &mut Vertices)
+         -> Vec<(usize, usize)> {
+    let mut pieces = vec![];
+    for (edge, splits) in edges.iter().zip(splits.iter_mut()) {
+        splits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let mut previous = vertices.index(edge.0);
+        for &(_, c) in splits.iter().chain(std::iter::once(&(1.0, edge.1))) {
+            let next = vertices.index(c);
+            if next != previous {
+                pieces.push((previous, next));
+                previous = next;
+            }
+        }
+    }
+    pieces
+}
+
+fn classify(pieces: &[(usize, usize)], other_pieces: &[(usize, usize)], other: &[Vec<Point>],
+            vertices: &[Point]) -> Vec<Side> {
+    pieces.iter().map(|&(from, to)| {
+        if other_pieces.contains(&(from, to)) {
+            Side::Same
+        } else if other_pieces.contains(&(to, from)) {
+            Side::Opposite
+        } else {
+            let (a, b) = (vertices[from], vertices[to]);
+            let middle = Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+            if polygon::contains_rings(other, middle) { Side::Inside } else { Side::Outside }
+        }
+    }).collect()
+}
+
+/// Link `pieces` into closed rings, always taking the leftmost turn where
+/// rings touch so every ring goes around a single region.
+fn link(pieces: &[(usize, usize)], vertices: &[Point]) -> Vec<Vec<Point>> {
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, &(from, _)) in pieces.iter().enumerate() {
+        outgoing.entry(from).or_default().push(i);
+    }
+    let direction = |i: usize| Vector2::from(&vertices[pieces[i].1]) - Vector2::from(&vertices[pieces[i].0]);
+    let mut used = vec![false; pieces.len()];
+    let mut rings = vec![];
+    for first in 0..pieces.len() {
+        if used[first] {
+            continue;
+        }
+        let start = pieces[first].0;
+        let mut ring = vec![];
+        let mut current = first;
+        loop {
+            used[current] = true;
+            ring.push(pieces[current].0);
+            let (end, d) = (pieces[current].1, direction(current));
+            if end == start {
+                rings.push(ring);
+                break;
+            }
+            let turn = |i: &usize| {
+                let e = direction(*i);
+                d.perp_dot_product(e).atan2(d.dot_product(e))
+            };
+            let next = outgoing.get(&end).and_then(|candidates| candidates.iter()
+                .filter(|&&i| !used[i])
+                .max_by(|a, b| turn(a).partial_cmp(&turn(b)).unwrap()));
+            match next {
+                Some(&next) => current = next,
+                // an open chain, only possible with invalid input
+                None => break,
+            }
+        }
+    }
+    rings.into_iter().map(|ring| simplify(ring.iter().map(|&i| vertices[i]).collect())).collect()
+}
+
+/// Remove corners where the ring goes straight on.
+fn simplify(ring: Vec<Point>) -> Vec<Point> {
+    let n = ring.len();
+    (0..n).filter(|&i| {
+        let a = Vector2::from(&ring[(i + n - 1) % n]);
+        let b = Vector2::from(&ring[i]);
+        let c = Vector2::from(&ring[(i + 1) % n]);
+        let (e1, e2) = (b - a, c - b);
+        e1.perp_dot_product(e2).abs() > EPSILON * e1.length() * e2.length() || e1.dot_product(e2) < 0.0
+    }).map(|i| ring[i]).collect()
+}
+
+/// Group rings into polygons: every counterclockwise ring is an outline,
+/// every clockwise ring a hole of the smallest outline around it.
+fn assemble(rings: Vec<Vec<Point>>) -> Vec<Vec<Vec<Point>>> {
+    let (mut outlines, holes): (Vec<_>, Vec<_>) = rings.into_iter()
+        .filter(|r| r.len() >= 3 && polygon::signed_area(r).abs() > EPSILON)
+        .partition(|r| polygon::signed_area(r) > 0.0);
+    outlines.sort_by(|a, b| polygon::signed_area(a).partial_cmp(&polygon::signed_area(b)).unwrap());
+    let mut polygons: Vec<Vec<Vec<Point>>> = outlines.into_iter().map(|o| vec![o]).collect();
+    for hole in holes {
+        let inside = |outline: &[Point]| hole.iter().zip(hole.iter().cycle().skip(1))
+            .map(|(a, b)| Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0))
+            .any(|m| polygon::contains(outline, m));
+        if let Some(p) = polygons.iter_mut().find(|p| inside(&p[0])) {
+            p.push(hole);
+        }
+    }
+    polygons
+}
+
+/// Apply `operation` to polygons `a` and `b`, giving zero or more polygons.
+///
+/// The rings of the operands may go either way around; in the result the
+/// outlines are counterclockwise and the holes clockwise.
+pub fn apply(operation: Operation, a: &[Vec<Point>], b: &[Vec<Point>]) -> Vec<Vec<Vec<Point>>> {
+    combine(operation, normalize(a), normalize(b))
+}
+
+/// Like `apply`, for sets of polygons that do not overlap each other.
+pub fn apply_all(operation: Operation, a: &[Vec<Vec<Point>>], b: &[Vec<Vec<Point>>])
+                 -> Vec<Vec<Vec<Point>>> {
+    let all = |polygons: &[Vec<Vec<Point>>]| polygons.iter().flat_map(|p| normalize(p)).collect();
+    combine(operation, all(a), all(b))
+}
+
+/// The union of `polygons`, which may overlap each other.
+pub fn union_all(polygons: &[Vec<Vec<Point>>]) -> Vec<Vec<Vec<Point>>> {
+    polygons.iter().fold(vec![], |union, p| apply_all(Operation::Union, &union, std::slice::from_ref(p)))
+}
+
+/// Apply `operation` to normalized rings.
+fn combine(operation: Operation, a: Vec<Vec<Point>>, b: Vec<Vec<Point>>) -> Vec<Vec<Vec<Point>>> {
+    let (a_edges, b_edges) = (edges(&a), edges(&b));
+    let mut a_splits = vec![vec![]; a_edges.len()];
+    let mut b_splits = vec![vec![]; b_edges.len()];
+    for (i, &ea) in a_edges.iter().enumerate() {
+        for (j, &eb) in b_edges.iter().enumerate() {
+            intersect(ea, eb, &mut a_splits[i], &mut b_splits[j]);
+        }
+    }
+    let mut vertices = Vertices(vec![]);
+    let a_pieces = split(&a_edges, &mut a_splits, &mut vertices);
+    let b_pieces = split(&b_edges, &mut b_splits, &mut vertices);
+    let a_sides = classify(&a_pieces, &b_pieces, &b, &vertices.0);
+    let b_sides = classify(&b_pieces, &a_pieces, &a, &vertices.0);
+
+    use Operation::*;
+    use Side::*;
+    let mut pieces = vec![];
+    for (&(from, to), side) in a_pieces.iter().zip(a_sides) {
+        match (operation, side) {
+            (Union, Outside) | (Union, Same) | (Intersection, Inside) | (Intersection, Same) |
+            (Difference, Outside) | (Difference, Opposite) | (Xor, Outside) => pieces.push((from, to)),
+            (Xor, Inside) => pieces.push((to, from)),
+            _ => (),
+        }
+    }
+    // shared edges are taken from `a`
+    for (&(from, to), side) in b_pieces.iter().zip(b_sides) {
+        match (operation, side) {
+            (Union, Outside) | (Intersection, Inside) | (Xor, Outside) => pieces.push((from, to)),
+            (Difference, Inside) | (Xor, Inside) => pieces.push((to, from)),
+            _ => (),
+        }
+    }
+    assemble(link(&pieces, &vertices.0))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Area, Color, Point};
+    use crate::boolean::{self, Operation};
+    use crate::polygon;
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Point> {
+        vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size)].into_iter().map(|c| c.into()).collect()
+    }
+
+    fn surface(polygons: &[Vec<Vec<Point>>]) -> f64 {
+        polygons.iter().flatten().map(|r| polygon::signed_area(r)).sum()
+    }
+
+    #[test]
+    fn overlapping() {
+        let a = vec![square(0.0, 0.0, 2.0)];
+        let b = vec![square(1.0, 1.0, 2.0)];
+        let union = boolean::apply(Operation::Union, &a, &b);
+        assert_eq!(1, union.len());
+        assert_eq!(8, union[0][0].len());
+        assert_eq!(7.0, surface(&union));
+        let intersection = boolean::apply(Operation::Intersection, &a, &b);
+        assert_eq!(1, intersection.len());
+        assert_eq!(1.0, surface(&intersection));
+        assert_eq!(4, intersection[0][0].len());
+        assert_eq!(3.0, surface(&boolean::apply(Operation::Difference, &a, &b)));
+        let xor = boolean::apply(Operation::Xor, &a, &b);
+        assert_eq!(2, xor.len());
+        assert_eq!(6.0, surface(&xor));
+    }
+
+    #[test]
+    fn holes() {
+        let plot = vec![square(0.0, 0.0, 10.0)];
+        let bed = vec![square(2.0, 2.0, 2.0)];
+        let lawn = boolean::apply(Operation::Difference, &plot, &bed);
+        assert_eq!(1, lawn.len());
+        assert_eq!(2, lawn[0].len());
+        assert_eq!(96.0, surface(&lawn));
+        // a bed across the border of the plot cuts a notch instead
+        let border = vec![square(8.0, 4.0, 4.0)];
+        let notched = boolean::apply(Operation::Difference, &lawn[0], &border);
+        assert_eq!(1, notched.len());
+        assert_eq!(88.0, surface(&notched));
+        // filling the hole again
+        let filled = boolean::apply(Operation::Union, &lawn[0], &bed);
+        assert_eq!(1, filled.len());
+        assert_eq!(1, filled[0].len());
+        assert_eq!(100.0, surface(&filled));
+        assert_eq!(4.0, surface(&boolean::apply(Operation::Intersection, &plot, &bed)));
+        assert!(boolean::apply(Operation::Intersection, &lawn[0], &bed).is_empty());
+    }
+
+    #[test]
+    fn areas() {
+        let plot = Area::new("lawn", vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]).fill(Color::Green);
+        let house = Area::new("house", vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+        let pond = Area::new("pond", vec![(7.0, 7.0), (8.0, 7.0), (8.0, 8.0), (7.0, 8.0)]);
+        let path = Area::new("path", vec![(4.0, 5.0), (10.0, 5.0), (10.0, 6.0), (4.0, 6.0)]);
+        let lawn = plot.difference(&house).into_iter()
+            .flat_map(|a| a.difference(&pond))
+            .flat_map(|a| a.difference(&path))
+            .collect::<Vec<_>>();
+        assert_eq!(1, lawn.len());
+        assert_eq!("lawn", lawn[0].name);
+        assert_eq!(Color::Green, lawn[0].fill);
+        assert_eq!(1, lawn[0].holes.len());
+        assert_eq!(100.0 - 16.0 - 1.0 - 6.0, lawn[0].surface());
+        let split = plot.difference(&Area::new("hedge", vec![(5.0, -1.0), (6.0, -1.0), (6.0, 11.0), (5.0, 11.0)]));
+        let names: Vec<&str> = split.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(vec!["lawn-1", "lawn-2"], names);
+    }
+
+    #[test]
+    fn union_all() {
+        let squares: Vec<_> = (0..4).map(|i| vec![square(i as f64, i as f64 * 0.5, 2.0)]).collect();
+        let union = boolean::union_all(&squares);
+        assert_eq!(1, union.len());
+        assert_eq!(4.0 * 4.0 - 3.0 * 1.0 * 1.5, surface(&union));
+        let apart = vec![vec![square(0.0, 0.0, 1.0)], vec![square(5.0, 0.0, 1.0)]];
+        let union = boolean::union_all(&apart);
+        assert_eq!(2, union.len());
+        let cut = boolean::apply_all(Operation::Difference, &union, &[vec![square(0.5, -1.0, 5.0)]]);
+        assert_eq!(1.0, surface(&cut));
+    }
+
+    #[test]
+    fn touching() {
+        let a = vec![square(0.0, 0.0, 1.0)];
+        let side = vec![square(1.0, 0.0, 1.0)];
+        let union = boolean::apply(Operation::Union, &a, &side);
+        assert_eq!(1, union.len());
+        assert_eq!(4, union[0][0].len());
+        assert_eq!(2.0, surface(&union));
+        assert!(boolean::apply(Operation::Intersection, &a, &side).is_empty());
+        let corner = vec![square(1.0, 1.0, 1.0)];
+        assert_eq!(2, boolean::apply(Operation::Union, &a, &corner).len());
+        let same = boolean::apply(Operation::Union, &a, &a);
+        assert_eq!(1.0, surface(&same));
+        assert!(boolean::apply(Operation::Difference, &a, &a).is_empty());
+        assert!(boolean::apply(Operation::Xor, &a, &a).is_empty());
+        // clockwise input
+        let reversed: Vec<Point> = a[0].iter().rev().cloned().collect();
+        assert_eq!(2.0, surface(&boolean::apply(Operation::Union, &[reversed], &side)));
+    }
+}
@@ -0,0 +1,83 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+use crate::matrix3::Matrix3;
+use crate::vector3::Vector3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    pub fn new(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> BoundingBox {
+        BoundingBox { min_x, min_y, max_x, max_y }
+    }
+
+    pub fn point(x: f64, y: f64) -> BoundingBox {
+        BoundingBox::new(x, y, x, y)
+    }
+
+    /// Smallest box containing all points, or `None` when there are no points.
+    pub fn from_points<I: IntoIterator<Item=(f64, f64)>>(points: I) -> Option<BoundingBox> {
+        points.into_iter()
+            .map(|(x, y)| BoundingBox::point(x, y))
+            .fold(None, |acc, b| Some(BoundingBox::union_opt(acc, b)))
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        ((self.min_x + self.max_x) / 2.0, (self.min_y + self.max_y) / 2.0)
+    }
+
+    pub fn union(self, other: BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    pub fn union_opt(a: Option<BoundingBox>, b: BoundingBox) -> BoundingBox {
+        match a {
+            Some(a) => a.union(b),
+            None => b,
+        }
+    }
+
+    /// Grow the box by `margin` on every side.
+    pub fn grow(self, margin: f64) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x - margin,
+            min_y: self.min_y - margin,
+            max_x: self.max_x + margin,
+            max_y: self.max_y + margin,
+        }
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+
+    pub fn corners(&self) -> [(f64, f64); 4] {
+        [(self.min_x, self.min_y), (self.max_x, self.min_y), (self.max_x, self.max_y), (self.min_x, self.max_y)]
+    }
+
+    /// Axis aligned box around the transformed corners of this box.
+    pub fn transform(&self, matrix: &Matrix3) -> BoundingBox {
+        BoundingBox::from_points(self.corners().iter().map(|&(x, y)| {
+            let v = Vector3::new(x, y, 1.0) * *matrix;
+            (v.x, v.y)
+        })).unwrap()
+    }
+}
@@ -1,7 +1,9 @@
 use std::fmt::{Display, Formatter, Error};
 
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Color {
     None,
     Black,
@@ -19,6 +21,28 @@ pub enum Color {
     LightGreen,
 }
 
+impl Color {
+    /// The SVG named color as RGB, `None` for `Color::None`.
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            Color::None => None,
+            Color::Black => Some((0, 0, 0)),
+            Color::Brown => Some((165, 42, 42)),
+            Color::Maroon => Some((128, 0, 0)),
+            Color::White => Some((255, 255, 255)),
+            Color::Red => Some((255, 0, 0)),
+            Color::Green => Some((0, 128, 0)),
+            Color::DarkGreen => Some((0, 100, 0)),
+            Color::Blue => Some((0, 0, 255)),
+            Color::Orange => Some((255, 165, 0)),
+            Color::Grey => Some((128, 128, 128)),
+            Color::LightGrey => Some((211, 211, 211)),
+            Color::DarkGrey => Some((169, 169, 169)),
+            Color::LightGreen => Some((144, 238, 144)),
+        }
+    }
+}
+
 impl Display for Color {
 
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
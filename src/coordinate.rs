@@ -1,8 +1,18 @@
 // (c) 2019 Joost Yervante Damad <joost@damad.be>
 
-use crate::matrix3::Matrix3;
-use crate::vector3::Vector3;
+//! The former combined position and frame type, kept for one release.
+//!
+//! `Coordinate` was split into `Point`, a position, and `Frame`, the
+//! placement of a world. Existing code keeps compiling: a `Coordinate`
+//! converts into either, so it can still be passed where a `Point` or a
+//! `Frame` is expected.
 
+#![allow(deprecated)]
+
+use crate::frame::Frame;
+use crate::point::Point;
+
+#[deprecated(note = "use `Point` for positions and `Frame` for world placements")]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Coordinate {
     pub x: f64,
@@ -57,45 +67,7 @@ impl Coordinate {
     }
 
     pub fn translate(self, x: f64, y: f64) -> Coordinate {
-        Coordinate {
-            x: self.x + x,
-            y: self.y + y,
-            ..self
-        }
-    }
-
-    pub fn matrix(&self) -> Matrix3 {
-        Matrix3::builder()
-            .scale(self.sx, self.sy)
-            .flip_x(self.fx)
-            //.flip_x(world.fx)
-            .flip_y(self.fy)
-            //.flip_y(world.fy)
-            .rotate(self.r)
-            //.rotate(world.r)
-            //.translate(world.into())
-            .build()
-    }
-
-    pub fn reference_to_world(self, world: &Coordinate) -> Coordinate {
-        let matrix = self.matrix();
-        let v1 = Vector3::new(self.x, self.y, 1.0);
-        let res = v1 * matrix;
-        Coordinate::new(res.x, res.y)
-            .translate(-world.x, -world.y) // ??
-            .rotate(-world.r) // ??
-            .flip_x_if(world.fx)
-            .flip_y_if(world.fy)
-            .scale_x(world.sx)
-            .scale_y(world.sy)
-    }
-
-    pub fn fix(self) -> Coordinate {
-        self.reference_to_world(&(0.0, 0.0).into())
-    }
-
-    pub fn distance(self, other: Coordinate) -> f64 {
-        (other.x - self.x) * (other.x - self.x) + (other.y - self.y) * (other.y - self.y).sqrt()
+        Coordinate { x: self.x + x, y: self.y + y, ..self }
     }
 }
 
@@ -105,46 +77,40 @@ impl From<(f64, f64)> for Coordinate {
     }
 }
 
-pub struct Coordinates;
-
-impl Coordinates {
-    pub fn axis_scale(coordinates: &Vec<Coordinate>) -> f64 {
-        let mut sort_x = coordinates.clone();
-        sort_x.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
-        let min_x = sort_x.first().unwrap().x;
-        let max_x = sort_x.last().unwrap().x;
-        let mut sort_y = coordinates.clone();
-        sort_y.sort_by(|a, b| a.y.partial_cmp(&b.y).unwrap());
-        let min_y = sort_y.first().unwrap().y;
-        let max_y = sort_y.last().unwrap().y;
-        let dx = max_x - min_x;
-        let dy = max_y - min_y;
-        let d = if dx < dy {
-            dx
-        } else {
-            dy
-        };
-        d / 10.0
+/// The position only; rotation, flips and scale are dropped.
+impl From<Coordinate> for Point {
+    fn from(c: Coordinate) -> Point {
+        Point::new(c.x, c.y)
+    }
+}
+
+impl From<Coordinate> for Frame {
+    fn from(c: Coordinate) -> Frame {
+        Frame {
+            origin: Point::new(c.x, c.y),
+            rotation: c.r,
+            flip_x: c.fx,
+            flip_y: c.fy,
+            scale_x: c.sx,
+            scale_y: c.sy,
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::Coordinate;
+    use crate::{Area, Frame, Point};
+    use crate::coordinate::Coordinate;
+    use crate::graphic::World;
 
     #[test]
-    fn flip_x() {
-        let c = Coordinate::new(10.0, 6.0);
-        let d = Coordinate::new(0.0, 0.0).flip_x();
-        let e = c.reference_to_world(&d);
-        assert_eq!(Coordinate::new(-10.0, 6.0), e);
+    fn conversions() {
+        let c = Coordinate::new(2.0, 3.0).rotate(90.0).flip_y().scale_x(2.0);
+        assert_eq!(Point::new(2.0, 3.0), c.into());
+        assert_eq!(Frame::new(2.0, 3.0).rotate(90.0).flip_y().scale_x(2.0), c.into());
+        // still accepted where points and frames are
+        let area = Area::new("bed", vec![Coordinate::new(0.0, 0.0), (4.0, 0.0).into(), (4.0, 3.0).into()]);
+        assert_eq!(Point::new(4.0, 3.0), area.corners[2]);
+        assert_eq!(Frame::from(c), World::new("shed", c).location);
     }
-
-    #[test]
-    fn flip_y() {
-        let c = Coordinate::new(10.0, 6.0);
-        let d = Coordinate::new(0.0, 0.0).flip_y();
-        let e = c.reference_to_world(&d);
-        assert_eq!(Coordinate::new(10.0, -6.0), e);
-    }
-}
\ No newline at end of file
+}
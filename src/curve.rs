@@ -0,0 +1,236 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! Curved edges of paths and areas: Bezier curves and elliptical arcs.
+
+use std::f64::consts::PI;
+
+use crate::Point;
+use crate::drawable;
+use crate::error::{self, Result};
+use crate::matrix3::Matrix3;
+
+/// Default maximum distance between a curve and its flattened polyline, in plan units.
+pub const TOLERANCE: f64 = 0.01;
+
+/// How an outline reaches a point from the point before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    /// Quadratic Bezier curve with a control point, SVG `Q`.
+    Quadratic(Point),
+    /// Cubic Bezier curve with two control points, SVG `C`.
+    Cubic(Point, Point),
+    /// Elliptical arc as in SVG `A`, with the rotation of the x axis in degrees.
+    Arc { rx: f64, ry: f64, rotation: f64, large_arc: bool, sweep: bool },
+}
+
+fn lerp(a: Point, b: Point, t: f64) -> Point {
+    Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+fn length(x: f64, y: f64) -> f64 {
+    (x * x + y * y).sqrt()
+}
+
+/// Number of equal steps in `t` keeping a curve with second derivative up to
+/// `d2` within `tolerance` of its chords.
+fn steps(d2: f64, tolerance: f64) -> usize {
+    ((d2 / (8.0 * tolerance)).sqrt().ceil() as usize).clamp(1, 1000)
+}
+
+impl Curve {
+    /// The curve with `matrix` applied to its control points.
+    ///
+    /// Arcs are exact for rotations, reflections and uniform scaling.
+    pub fn transform(&self, matrix: &Matrix3) -> Curve {
+        match *self {
+            Curve::Quadratic(c) => Curve::Quadratic(c.transform(matrix)),
+            Curve::Cubic(c1, c2) => Curve::Cubic(c1.transform(matrix), c2.transform(matrix)),
+            Curve::Arc { rx, ry, rotation, large_arc, sweep } => {
+                let scale = drawable::scale_factor(matrix);
+                let origin = Point::new(0.0, 0.0).transform(matrix);
+                let r = rotation.to_radians();
+                let axis = Point::new(r.cos(), r.sin()).transform(matrix);
+                let reflected = matrix.m11 * matrix.m22 - matrix.m12 * matrix.m21 < 0.0;
+                Curve::Arc {
+                    rx: rx * scale,
+                    ry: ry * scale,
+                    rotation: (axis.y - origin.y).atan2(axis.x - origin.x).to_degrees(),
+                    large_arc,
+                    sweep: sweep != reflected,
+                }
+            }
+        }
+    }
+
+    /// Points approximating the curve from `from` to `to` within `tolerance`,
+    /// excluding `from` and ending exactly at `to`.
+    pub fn flatten(&self, from: Point, to: Point, tolerance: f64) -> Vec<Point> {
+        let mut points = match *self {
+            Curve::Quadratic(c) => {
+                let d2 = 2.0 * length(from.x - 2.0 * c.x + to.x, from.y - 2.0 * c.y + to.y);
+                let n = steps(d2, tolerance);
+                (1..n).map(|i| {
+                    let t = i as f64 / n as f64;
+                    lerp(lerp(from, c, t), lerp(c, to, t), t)
+                }).collect()
+            }
+            Curve::Cubic(c1, c2) => {
+                let d2 = 6.0 * length(from.x - 2.0 * c1.x + c2.x, from.y - 2.0 * c1.y + c2.y)
+                    .max(length(c1.x - 2.0 * c2.x + to.x, c1.y - 2.0 * c2.y + to.y));
+                let n = steps(d2, tolerance);
+                (1..n).map(|i| {
+                    let t = i as f64 / n as f64;
+                    let (a, b, c) = (lerp(from, c1, t), lerp(c1, c2, t), lerp(c2, to, t));
+                    lerp(lerp(a, b, t), lerp(b, c, t), t)
+                }).collect()
+            }
+            Curve::Arc { rx, ry, rotation, large_arc, sweep } =>
+                arc_points(from, to, rx, ry, rotation, large_arc, sweep, tolerance),
+        };
+        points.push(to);
+        points
+    }
+
+    /// Check that the control points and radii are finite.
+    pub(crate) fn validate(&self, element: &str) -> Result<()> {
+        match self {
+            Curve::Quadratic(c) => error::check_coordinate(element, "control point", c),
+            Curve::Cubic(c1, c2) => {
+                error::check_coordinate(element, "control point", c1)?;
+                error::check_coordinate(element, "control point", c2)
+            }
+            Curve::Arc { rx, ry, rotation, .. } => {
+                error::check_size(element, "arc radius", *rx)?;
+                error::check_size(element, "arc radius", *ry)?;
+                error::check_finite(element, "arc rotation", *rotation)
+            }
+        }
+    }
+}
+
+fn angle(ux: f64, uy: f64, vx: f64, vy: f64) -> f64 {
+    (ux * vy - uy * vx).atan2(ux * vx + uy * vy)
+}
+
+/// Intermediate points of an SVG arc, converted to center parameterization
+/// as described in the SVG implementation notes.
+#[allow(clippy::too_many_arguments)]
+fn arc_points(from: Point, to: Point, rx: f64, ry: f64, rotation: f64,
+              large_arc: bool, sweep: bool, tolerance: f64) -> Vec<Point> {
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 || from == to {
+        return vec![];
+    }
+    let phi = rotation.to_radians();
+    let (sin, cos) = phi.sin_cos();
+    let (hx, hy) = ((from.x - to.x) / 2.0, (from.y - to.y) / 2.0);
+    let x1 = cos * hx + sin * hy;
+    let y1 = -sin * hx + cos * hy;
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coef = sign * (num / den).max(0.0).sqrt();
+    let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+    let cx = cos * cx1 - sin * cy1 + (from.x + to.x) / 2.0;
+    let cy = sin * cx1 + cos * cy1 + (from.y + to.y) / 2.0;
+    let theta = angle(1.0, 0.0, (x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut delta = angle((x1 - cx1) / rx, (y1 - cy1) / ry, (-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    }
+    let r = rx.max(ry);
+    let step = if tolerance < r { 2.0 * (1.0 - tolerance / r).acos() } else { PI / 2.0 };
+    let n = ((delta.abs() / step).ceil() as usize).clamp(1, 1000);
+    (1..n).map(|i| {
+        let t = theta + delta * i as f64 / n as f64;
+        let (s, c) = t.sin_cos();
+        Point::new(cx + rx * c * cos - ry * s * sin, cy + rx * c * sin + ry * s * cos)
+    }).collect()
+}
+
+/// The outline through `points` with its curved edges flattened.
+///
+/// `curves[i]` is the edge arriving at `points[i]`, straight when it is
+/// `None` or missing. A closed outline also has the edge from the last point
+/// back to the first, described by `curves[0]`; its polyline does not repeat
+/// the first point at the end.
+pub fn flatten(points: &[Point], curves: &[Option<Curve>], closed: bool, tolerance: f64) -> Vec<Point> {
+    let curve = |i: usize| curves.get(i).copied().flatten();
+    let mut result = match points.first() {
+        Some(first) => vec![*first],
+        None => return vec![],
+    };
+    for i in 1..points.len() {
+        match curve(i) {
+            Some(c) => result.extend(c.flatten(points[i - 1], points[i], tolerance)),
+            None => result.push(points[i]),
+        }
+    }
+    if let (true, Some(c)) = (closed && points.len() > 1, curve(0)) {
+        let mut closing = c.flatten(points[points.len() - 1], points[0], tolerance);
+        closing.pop();
+        result.extend(closing);
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::Point;
+    use crate::curve::{self, Curve};
+    use crate::matrix3::Matrix3;
+
+    #[test]
+    fn half_circle() {
+        let arc = Curve::Arc { rx: 5.0, ry: 5.0, rotation: 0.0, large_arc: false, sweep: true };
+        let points = arc.flatten(Point::new(-5.0, 0.0), Point::new(5.0, 0.0), 0.001);
+        assert!(points.len() > 10);
+        for p in &points {
+            assert_approx_eq!(p.x.hypot(p.y), 5.0);
+            assert!(p.y < 1e-9);
+        }
+        assert_eq!(Point::new(5.0, 0.0), *points.last().unwrap());
+    }
+
+    #[test]
+    fn bezier_within_tolerance() {
+        let cubic = Curve::Cubic(Point::new(0.0, 10.0), Point::new(10.0, 10.0));
+        let points = cubic.flatten(Point::new(0.0, 0.0), Point::new(10.0, 0.0), 0.01);
+        // the middle of this curve is at (5, 7.5)
+        assert!(points.iter().any(|p| (p.x - 5.0).abs() < 0.5 && (p.y - 7.5).abs() < 0.05));
+        let quadratic = Curve::Quadratic(Point::new(5.0, 0.0));
+        assert_eq!(1, quadratic.flatten(Point::new(0.0, 0.0), Point::new(10.0, 0.0), 0.01).len());
+    }
+
+    #[test]
+    fn closed_outline() {
+        let corners = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0)];
+        let arc = Curve::Arc { rx: 5.0, ry: 5.0, rotation: 0.0, large_arc: false, sweep: true };
+        let ring = curve::flatten(&corners, &[Some(arc)], true, 0.01);
+        assert_eq!(corners[..], ring[..2]);
+        assert!(ring[2..].iter().all(|p| p.y > 0.0));
+    }
+
+    #[test]
+    fn transform_arc() {
+        let arc = Curve::Arc { rx: 2.0, ry: 1.0, rotation: 0.0, large_arc: false, sweep: true };
+        let matrix = Matrix3::rotate(90.0) * Matrix3::scale(2.0, -2.0);
+        match arc.transform(&matrix) {
+            Curve::Arc { rx, ry, sweep, .. } => {
+                assert_approx_eq!(rx, 4.0);
+                assert_approx_eq!(ry, 2.0);
+                assert!(!sweep);
+            }
+            c => panic!("unexpected {:?}", c),
+        }
+    }
+}
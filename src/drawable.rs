@@ -0,0 +1,109 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+use std::any::Any;
+use std::fmt::Debug;
+
+use simple_xml_serialize::XMLElement;
+
+use crate::bounding_box::BoundingBox;
+use crate::matrix3::Matrix3;
+
+/// Common interface of everything that can be drawn in a plan.
+///
+/// All built-in graphics implement it, and user types implementing it can be
+/// added to a `Document` or `World` through `Element::Custom`.
+pub trait Drawable: Debug {
+    /// The name of the drawable, if it has one.
+    fn name(&self) -> Option<&str>;
+
+    /// Bounding box in the coordinates of the parent, or `None` if nothing is drawn.
+    fn bounding_box(&self) -> Option<BoundingBox>;
+
+    /// Transform all geometry with `matrix`.
+    ///
+    /// Radii and sizes are scaled with the average scale of the matrix.
+    fn transform(&mut self, matrix: &Matrix3);
+
+    /// Render to SVG.
+    fn render(&self) -> XMLElement;
+
+    fn box_clone(&self) -> Box<dyn Drawable>;
+
+    /// `self` as `Any`, for downcasting in `eq_dyn`.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Whether `other` is of the same type and equal to `self`; usually
+    /// `other.as_any().downcast_ref::<Self>() == Some(self)`.
+    fn eq_dyn(&self, other: &dyn Drawable) -> bool;
+}
+
+impl Clone for Box<dyn Drawable> {
+    fn clone(&self) -> Box<dyn Drawable> {
+        self.box_clone()
+    }
+}
+
+impl PartialEq for Box<dyn Drawable> {
+    fn eq(&self, other: &Box<dyn Drawable>) -> bool {
+        self.eq_dyn(other.as_ref())
+    }
+}
+
+/// Average scale factor of the linear part of `matrix`.
+pub(crate) fn scale_factor(matrix: &Matrix3) -> f64 {
+    (matrix.m11 * matrix.m22 - matrix.m12 * matrix.m21).abs().sqrt()
+}
+
+#[cfg(test)]
+mod test {
+    use std::any::Any;
+
+    use simple_xml_serialize::XMLElement;
+
+    use crate::{BoundingBox, Circle, Color, Drawable};
+    use crate::matrix3::Matrix3;
+
+    /// Renders the same whatever its weight.
+    #[derive(Debug, Clone, PartialEq)]
+    struct Marker {
+        weight: f64,
+    }
+
+    impl Drawable for Marker {
+        fn name(&self) -> Option<&str> {
+            None
+        }
+
+        fn bounding_box(&self) -> Option<BoundingBox> {
+            None
+        }
+
+        fn transform(&mut self, _matrix: &Matrix3) {}
+
+        fn render(&self) -> XMLElement {
+            XMLElement::new("g")
+        }
+
+        fn box_clone(&self) -> Box<dyn Drawable> {
+            Box::new(self.clone())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn eq_dyn(&self, other: &dyn Drawable) -> bool {
+            other.as_any().downcast_ref::<Self>() == Some(self)
+        }
+    }
+
+    #[test]
+    fn equality() {
+        let light: Box<dyn Drawable> = Box::new(Marker { weight: 1.0 });
+        let heavy: Box<dyn Drawable> = Box::new(Marker { weight: 2.0 });
+        assert!(light == light.clone());
+        assert!(light != heavy);
+        let circle: Box<dyn Drawable> = Box::new(Circle::new(0.0, 0.0, 1.0, Color::Black, Color::None));
+        assert!(light != circle);
+    }
+}
@@ -0,0 +1,289 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! ASCII DXF output for CAD packages.
+//!
+//! Coordinates are written in plan units with all `World` transforms applied;
+//! every world becomes a layer. The file is in the R12 format (`AC1009`),
+//! which needs no entity handles or objects, so only the header, the line
+//! type and layer tables and the entities are written.
+
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::{Color, Document};
+use crate::backend::{self, Backend};
+use crate::matrix3::Matrix3;
+
+/// AutoCAD color index, 256 is BYLAYER.
+fn aci(color: Color) -> i32 {
+    match color {
+        Color::None => 256,
+        Color::Black | Color::White => 7,
+        Color::Red => 1,
+        Color::Green => 3,
+        Color::DarkGreen => 94,
+        Color::LightGreen => 81,
+        Color::Blue => 5,
+        Color::Orange => 30,
+        Color::Brown => 34,
+        Color::Maroon => 16,
+        Color::Grey => 8,
+        Color::LightGrey => 9,
+        Color::DarkGrey => 250,
+    }
+}
+
+/// Layer names are limited to letters, digits, `-` and `_`.
+fn layer_name(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect()
+}
+
+/// Text values are a single line of ASCII: line breaks become spaces and
+/// other characters are written as `\U+XXXX` escapes.
+fn text_value(text: &str) -> String {
+    let mut value = String::new();
+    for line in text.lines() {
+        if !value.is_empty() {
+            value.push(' ');
+        }
+        for c in line.chars() {
+            match c {
+                '\r' | '\n' => value.push(' '),
+                c if c.is_ascii() => value.push(c),
+                c => write!(value, "\\U+{:04X}", c as u32).unwrap(),
+            }
+        }
+    }
+    value
+}
+
+struct Dxf {
+    stack: Vec<String>,
+    layers: Vec<String>,
+    entities: String,
+}
+
+impl Dxf {
+    fn pair<T: std::fmt::Display>(&mut self, code: i32, value: T) {
+        write!(self.entities, "{:>3}\n{}\n", code, value).unwrap();
+    }
+
+    fn entity(&mut self, kind: &str, color: Color) {
+        let layer = self.layer();
+        self.pair(0, kind);
+        self.pair(8, layer);
+        self.pair(62, aci(color));
+    }
+
+    fn layer(&self) -> String {
+        self.stack.last().cloned().unwrap_or_else(|| "0".into())
+    }
+
+    /// A `POLYLINE` followed by its `VERTEX` entities and a `SEQEND`.
+    fn polyline_entity(&mut self, points: &[(f64, f64)], closed: bool, color: Color) {
+        let layer = self.layer();
+        self.entity("POLYLINE", color);
+        self.pair(66, 1);
+        self.pair(10, 0.0);
+        self.pair(20, 0.0);
+        self.pair(30, 0.0);
+        self.pair(70, if closed { 1 } else { 0 });
+        for &(x, y) in points {
+            self.pair(0, "VERTEX");
+            self.pair(8, &layer);
+            self.pair(10, x);
+            self.pair(20, y);
+            self.pair(30, 0.0);
+        }
+        self.pair(0, "SEQEND");
+        self.pair(8, layer);
+    }
+}
+
+impl Backend for Dxf {
+    fn polygon(&mut self, rings: &[Vec<(f64, f64)>], stroke: Color, fill: Color, _stroke_width: f64) {
+        for ring in rings {
+            self.polyline_entity(ring, true, if stroke == Color::None { fill } else { stroke });
+        }
+    }
+
+    fn polyline(&mut self, points: &[(f64, f64)], stroke: Color, _stroke_width: f64, _dash: &[f64]) {
+        if points.len() == 2 {
+            self.entity("LINE", stroke);
+            self.pair(10, points[0].0);
+            self.pair(20, points[0].1);
+            self.pair(11, points[1].0);
+            self.pair(21, points[1].1);
+        } else {
+            self.polyline_entity(points, false, stroke);
+        }
+    }
+
+    fn circle(&mut self, (x, y): (f64, f64), r: f64, stroke: Color, fill: Color, _stroke_width: f64) {
+        self.entity("CIRCLE", if stroke == Color::None { fill } else { stroke });
+        self.pair(10, x);
+        self.pair(20, y);
+        self.pair(40, r);
+    }
+
+    fn text(&mut self, (x, y): (f64, f64), size: f64, text: &str) {
+        self.entity("TEXT", Color::Black);
+        self.pair(10, x);
+        self.pair(20, y);
+        self.pair(40, size);
+        self.pair(1, text_value(text));
+        self.pair(72, 1);
+        self.pair(11, x);
+        self.pair(21, y);
+    }
+
+    fn begin_world(&mut self, name: &str) {
+        let layer = layer_name(name);
+        if !self.layers.contains(&layer) {
+            self.layers.push(layer.clone());
+        }
+        self.stack.push(layer);
+    }
+
+    fn end_world(&mut self) {
+        self.stack.pop();
+    }
+}
+
+/// Write the document as DXF.
+pub fn write_dxf<W: Write>(document: &Document, mut out: W) -> io::Result<()> {
+    let mut dxf = Dxf { stack: vec![], layers: vec![], entities: String::new() };
+    backend::draw(document, &Matrix3::unit(), &mut dxf);
+    let mut s = String::new();
+    s.push_str("  0\nSECTION\n  2\nHEADER\n  9\n$ACADVER\n  1\nAC1009\n  0\nENDSEC\n");
+    s.push_str("  0\nSECTION\n  2\nTABLES\n");
+    s.push_str("  0\nTABLE\n  2\nLTYPE\n 70\n1\n");
+    s.push_str("  0\nLTYPE\n  2\nCONTINUOUS\n 70\n0\n  3\nSolid line\n 72\n65\n 73\n0\n 40\n0.0\n");
+    s.push_str("  0\nENDTAB\n  0\nTABLE\n  2\nLAYER\n");
+    write!(s, " 70\n{}\n", dxf.layers.len()).unwrap();
+    for layer in &dxf.layers {
+        write!(s, "  0\nLAYER\n  2\n{}\n 70\n0\n 62\n7\n  6\nCONTINUOUS\n", layer).unwrap();
+    }
+    s.push_str("  0\nENDTAB\n  0\nENDSEC\n");
+    s.push_str("  0\nSECTION\n  2\nENTITIES\n");
+    s.push_str(&dxf.entities);
+    s.push_str("  0\nENDSEC\n  0\nEOF\n");
+    out.write_all(s.as_bytes())
+}
+
+impl Document {
+    /// Write the document as DXF to `filename`.
+    pub fn save_dxf(&self, filename: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(filename)?);
+        write_dxf(self, &mut out)?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Area, Point, Document, Label};
+    use crate::dxf;
+    use crate::tree::Tree;
+
+    /// The group code and value pairs of a DXF file.
+    fn pairs(text: &str) -> Vec<(i32, &str)> {
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(0, lines.len() % 2);
+        lines.chunks(2).map(|p| (p[0].trim().parse().expect("group code"), p[1])).collect()
+    }
+
+    /// The entities of a section, each a list of pairs starting with its type;
+    /// header variables come before the first entity.
+    fn section<'a>(pairs: &[(i32, &'a str)], name: &str) -> Vec<Vec<(i32, &'a str)>> {
+        let start = pairs.windows(2).position(|w| w == [(0, "SECTION"), (2, name)]).expect("section") + 2;
+        let end = start + pairs[start..].iter().position(|&p| p == (0, "ENDSEC")).expect("end of section");
+        let mut entities: Vec<Vec<(i32, &str)>> = vec![];
+        for &pair in &pairs[start..end] {
+            match (pair, entities.last_mut()) {
+                ((0, _), _) | (_, None) => entities.push(vec![pair]),
+                (_, Some(entity)) => entity.push(pair),
+            }
+        }
+        entities
+    }
+
+    fn value<'a>(entity: &[(i32, &'a str)], code: i32) -> Option<&'a str> {
+        entity.iter().find(|p| p.0 == code).map(|p| p.1)
+    }
+
+    #[test]
+    fn entities() {
+        let mut doc = Document::new(0.0, 0.0, 40.0, 30.0, 10);
+        let mut area = Area::new("bed 1", vec![(0.0, 0.0), (4.0, 0.0), (4.0, 3.0)])
+            .world(Point::new(10.0, 0.0), None);
+        area.add(Tree {
+            name: "apple".into(),
+            species: "Malus domestica".into(),
+            trunk_diameter: 0.2,
+            crown_diameter: Some(3.0),
+            location: Point::new(1.0, 2.0),
+            label_location: Point::new(1.0, 0.0),
+        });
+        doc.add(area);
+        let mut out = vec![];
+        dxf::write_dxf(&doc, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let pairs = pairs(&text);
+        assert_eq!(Some(&(0, "EOF")), pairs.last());
+        let sections: Vec<&str> = pairs.windows(2).filter(|w| w[0] == (0, "SECTION")).map(|w| w[1].1).collect();
+        assert_eq!(vec!["HEADER", "TABLES", "ENTITIES"], sections);
+        let header = section(&pairs, "HEADER");
+        assert_eq!(Some("AC1009"), value(&header[0], 1));
+        // R12 has no handles or subclass markers
+        assert!(pairs.iter().all(|p| p.0 != 5 && p.0 != 100));
+
+        let tables = section(&pairs, "TABLES");
+        let ltypes: Vec<&str> = tables.iter().filter(|e| e[0].1 == "LTYPE").filter_map(|e| value(e, 2)).collect();
+        assert_eq!(vec!["CONTINUOUS"], ltypes);
+        let layers: Vec<&[(i32, &str)]> = tables.iter().filter(|e| e[0].1 == "LAYER").map(|e| &e[..]).collect();
+        assert!(layers.iter().all(|l| ltypes.contains(&value(l, 6).unwrap())));
+        let layers: Vec<&str> = layers.iter().filter_map(|l| value(l, 2)).collect();
+        assert_eq!(vec!["Document", "world-bed_1"], layers);
+
+        let entities = section(&pairs, "ENTITIES");
+        assert!(entities.iter().all(|e| layers.contains(&value(e, 8).unwrap())));
+        // every polyline is followed by its vertices and a SEQEND
+        let mut i = 0;
+        let mut polylines = vec![];
+        while i < entities.len() {
+            if entities[i][0].1 == "POLYLINE" {
+                assert_eq!(Some("1"), value(&entities[i], 66));
+                let n = entities[i + 1..].iter().take_while(|e| e[0].1 == "VERTEX").count();
+                assert_eq!("SEQEND", entities[i + 1 + n][0].1);
+                polylines.push((value(&entities[i], 8).unwrap(), value(&entities[i], 70).unwrap(), n));
+                i += n + 1;
+            } else {
+                assert!(["LINE", "CIRCLE", "TEXT"].contains(&entities[i][0].1), "{:?}", entities[i]);
+            }
+            i += 1;
+        }
+        assert_eq!(vec![("Document", "1", 3)], polylines);
+        // crown and trunk, placed at the world origin
+        let circles: Vec<_> = entities.iter().filter(|e| e[0].1 == "CIRCLE").collect();
+        assert_eq!(2, circles.len());
+        assert!(circles.iter().all(|c| value(c, 8) == Some("world-bed_1")));
+        assert_eq!((Some("11"), Some("2"), Some("1.5")), (value(circles[0], 10), value(circles[0], 20),
+                                                          value(circles[0], 40)));
+        assert!(entities.iter().any(|e| e[0].1 == "TEXT" && value(e, 1) == Some("apple")));
+    }
+
+    #[test]
+    fn text() {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        doc.add(Label::new(Point::new(1.0, 1.0), "Bed 1\r\nrosé\nand lilies"));
+        let mut out = vec![];
+        dxf::write_dxf(&doc, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let pairs = pairs(&text);
+        let labels: Vec<&str> = section(&pairs, "ENTITIES").iter()
+            .filter(|e| e[0].1 == "TEXT").filter_map(|e| value(e, 1)).collect();
+        assert_eq!(vec!["Bed 1 ros\\U+00E9 and lilies"], labels);
+    }
+}
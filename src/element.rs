@@ -0,0 +1,544 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+use std::any::Any;
+use std::slice;
+
+use simple_xml_serialize::XMLElement;
+
+use crate::Point;
+use crate::frame::Frame;
+use crate::bounding_box::BoundingBox;
+use crate::curve;
+use crate::drawable::{self, Drawable};
+use crate::error::Result;
+use crate::graphic::{Area, Circle, Line, Path, World};
+use crate::matrix3::Matrix3;
+use crate::path_data::PathFormat;
+use crate::polygon;
+use crate::text::Label;
+use crate::tree::Tree;
+
+/// A node in the scene graph.
+///
+/// Elements keep their typed model until the document is rendered, so a plan
+/// can be inspected and modified after it has been built.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    Path(Path),
+    Area(Area),
+    Circle(Circle),
+    Line(Line),
+    Label(Label),
+    Tree(Tree),
+    World(World),
+    /// A user defined drawable.
+    Custom(Box<dyn Drawable>),
+}
+
+impl Element {
+    pub fn as_drawable(&self) -> &dyn Drawable {
+        match self {
+            Element::Path(x) => x,
+            Element::Area(x) => x,
+            Element::Circle(x) => x,
+            Element::Line(x) => x,
+            Element::Label(x) => x,
+            Element::Tree(x) => x,
+            Element::World(x) => x,
+            Element::Custom(x) => x.as_ref(),
+        }
+    }
+
+    pub fn as_drawable_mut(&mut self) -> &mut dyn Drawable {
+        match self {
+            Element::Path(x) => x,
+            Element::Area(x) => x,
+            Element::Circle(x) => x,
+            Element::Line(x) => x,
+            Element::Label(x) => x,
+            Element::Tree(x) => x,
+            Element::World(x) => x,
+            Element::Custom(x) => x.as_mut(),
+        }
+    }
+
+    /// The name of the element, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.as_drawable().name()
+    }
+
+    /// Check the geometry of the element and its children.
+    ///
+    /// `Custom` elements are not checked.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Element::Path(p) => p.validate(),
+            Element::Area(a) => a.validate(),
+            Element::Circle(c) => c.validate(),
+            Element::Line(l) => l.validate(),
+            Element::Label(l) => l.validate(),
+            Element::Tree(t) => t.validate(),
+            Element::World(w) => w.validate(),
+            Element::Custom(_) => Ok(()),
+        }
+    }
+
+    /// The SVG id the element is rendered with, if it has one.
+    pub fn id(&self) -> Option<String> {
+        match self {
+            Element::Path(p) => Some(format!("area-{}", p.name)),
+            Element::Area(a) => Some(format!("area-{}", a.name)),
+            Element::Tree(t) => Some(format!("tree-{}", t.name)),
+            Element::World(w) => Some(format!("world-{}", w.name)),
+            Element::Circle(_) | Element::Line(_) | Element::Label(_) | Element::Custom(_) => None,
+        }
+    }
+
+    /// The direct children of the element.
+    ///
+    /// Only a `World`, or an `Area` that has a world, has children.
+    pub fn children(&self) -> &[Element] {
+        match self {
+            Element::World(w) => &w.elements,
+            Element::Area(Area { world: Some(w), .. }) => &w.elements,
+            _ => &[],
+        }
+    }
+
+    pub fn children_mut(&mut self) -> &mut [Element] {
+        match self {
+            Element::World(w) => &mut w.elements,
+            Element::Area(Area { world: Some(w), .. }) => &mut w.elements,
+            _ => &mut [],
+        }
+    }
+
+    /// Depth-first iterator over this element and all its descendants.
+    pub fn walk(&self) -> Walk<'_> {
+        Walk { stack: vec![self] }
+    }
+
+    /// Find the first element, this one included, with the given name.
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.walk().find(|e| e.name() == Some(name))
+    }
+
+    /// Find the first element, this one included, with the given name for modification.
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Element> {
+        if self.name() == Some(name) {
+            return Some(self);
+        }
+        find_mut(self.children_mut(), name)
+    }
+
+    /// Find the first element, this one included, with the given SVG id.
+    pub fn find_id(&self, id: &str) -> Option<&Element> {
+        self.walk().find(|e| e.id().as_deref() == Some(id))
+    }
+
+    /// Where the children are placed in the coordinates of this element,
+    /// `None` when it has no world.
+    pub fn child_frame(&self) -> Option<Frame> {
+        match self {
+            Element::World(w) => Some(w.location),
+            Element::Area(Area { world: Some(w), .. }) => Some(w.location),
+            _ => None,
+        }
+    }
+
+    /// The matrix mapping the coordinates of the children to those of this element.
+    pub fn child_matrix(&self) -> Matrix3 {
+        self.child_frame().map_or_else(Matrix3::unit, |f| f.matrix())
+    }
+
+    /// Whether `point` is on the element placed by `matrix`, not counting its
+    /// children: inside an area, circle or tree, or within half the stroke
+    /// width of a path or line. `stroke_width` is the width of paths and
+    /// lines without a stroke width of their own.
+    pub fn hit(&self, point: Point, matrix: &Matrix3, stroke_width: f64) -> bool {
+        let place = |c: &Point| c.transform(matrix);
+        let scale = drawable::scale_factor(matrix);
+        match self {
+            Element::Path(p) => {
+                let points: Vec<Point> = p.flatten(curve::TOLERANCE).iter().map(place).collect();
+                polygon::polyline_distance(&points, point) <= p.stroke_width.unwrap_or(stroke_width) * scale / 2.0
+            }
+            Element::Area(a) => {
+                let rings: Vec<Vec<Point>> = a.rings(curve::TOLERANCE).iter()
+                    .map(|r| r.iter().map(place).collect())
+                    .collect();
+                polygon::contains_rings(&rings, point)
+            }
+            Element::Circle(c) => {
+                let mut c = c.clone();
+                c.transform(matrix);
+                c.contains(point)
+            }
+            Element::Line(l) => {
+                let distance = polygon::segment_distance(point, place(&l.p1), place(&l.p2));
+                distance <= l.stroke_width.unwrap_or(stroke_width) * scale / 2.0
+            }
+            Element::Tree(t) => {
+                let mut t = t.clone();
+                t.transform(matrix);
+                t.contains(point)
+            }
+            Element::Label(_) | Element::World(_) | Element::Custom(_) => false,
+        }
+    }
+
+    /// The stroke width children inherit, given the one this element inherits.
+    fn child_stroke_width(&self, stroke_width: f64) -> f64 {
+        match self {
+            Element::World(w) => w.stroke_width.unwrap_or(stroke_width),
+            Element::Area(Area { world: Some(w), .. }) => w.stroke_width.unwrap_or(stroke_width),
+            _ => stroke_width,
+        }
+    }
+
+    /// Find the first element, this one included, with the given name, and
+    /// return a copy placed by the worlds it is in, in the coordinates this
+    /// element is in. Measurements on the copy are in those units.
+    pub fn find_placed(&self, name: &str) -> Option<Element> {
+        if self.name() == Some(name) {
+            return Some(self.clone());
+        }
+        find_placed(self.children(), name, &self.child_matrix())
+    }
+
+    /// The frames of the worlds the first element with the given name is in,
+    /// inside this element, outermost first; empty when it is this element.
+    /// `Frame::chain` of them maps its coordinates to those of this element.
+    pub fn frames(&self, name: &str) -> Option<Vec<Frame>> {
+        let mut stack = vec![];
+        if self.name() == Some(name) || frames(slice::from_ref(self), name, &mut stack) {
+            Some(stack)
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) fn find_mut<'a>(elements: &'a mut [Element], name: &str) -> Option<&'a mut Element> {
+    elements.iter_mut().find_map(|e| e.find_mut(name))
+}
+
+/// See `Element::find_placed`, `matrix` maps the coordinates of `elements` to the result.
+pub(crate) fn find_placed(elements: &[Element], name: &str, matrix: &Matrix3) -> Option<Element> {
+    elements.iter().find_map(|e| if e.name() == Some(name) {
+        let mut placed = e.clone();
+        placed.transform(matrix);
+        Some(placed)
+    } else {
+        find_placed(e.children(), name, &(e.child_matrix() * *matrix))
+    })
+}
+
+/// See `Document::frame`, `matrix` maps the coordinates of `elements` to the result.
+pub(crate) fn frame(elements: &[Element], name: &str, matrix: &Matrix3) -> Option<Matrix3> {
+    elements.iter().find_map(|e| {
+        let m = e.child_matrix() * *matrix;
+        if e.child_frame().is_some() && e.name() == Some(name) { Some(m) } else { frame(e.children(), name, &m) }
+    })
+}
+
+/// See `Element::frames`, push the frames of the worlds the first element
+/// with the given name is in on `stack`; `false` when there is none.
+pub(crate) fn frames(elements: &[Element], name: &str, stack: &mut Vec<Frame>) -> bool {
+    elements.iter().any(|e| {
+        if e.name() == Some(name) {
+            return true;
+        }
+        let depth = stack.len();
+        stack.extend(e.child_frame());
+        if frames(e.children(), name, stack) {
+            return true;
+        }
+        stack.truncate(depth);
+        false
+    })
+}
+
+/// Add the names of the elements hit at `point`, see `Element::hit`, and
+/// of their descendants to `hits`, depth-first; `matrix` maps the
+/// coordinates of `elements` to those of `point`.
+pub(crate) fn hit_test(elements: &[Element], point: Point, matrix: &Matrix3, stroke_width: f64,
+                       hits: &mut Vec<String>) {
+    for e in elements {
+        if let (true, Some(name)) = (e.hit(point, matrix, stroke_width), e.name()) {
+            hits.push(name.to_string());
+        }
+        hit_test(e.children(), point, &(e.child_matrix() * *matrix), e.child_stroke_width(stroke_width), hits);
+    }
+}
+
+/// Depth-first iterator over a tree of elements, see `Element::walk`.
+pub struct Walk<'a> {
+    stack: Vec<&'a Element>,
+}
+
+impl<'a> Walk<'a> {
+    pub(crate) fn new(elements: &'a [Element]) -> Walk<'a> {
+        Walk { stack: elements.iter().rev().collect() }
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = &'a Element;
+
+    fn next(&mut self) -> Option<&'a Element> {
+        let e = self.stack.pop()?;
+        self.stack.extend(e.children().iter().rev());
+        Some(e)
+    }
+}
+
+impl Drawable for Element {
+    fn name(&self) -> Option<&str> {
+        self.as_drawable().name()
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.as_drawable().bounding_box()
+    }
+
+    fn transform(&mut self, matrix: &Matrix3) {
+        self.as_drawable_mut().transform(matrix)
+    }
+
+    fn render(&self) -> XMLElement {
+        self.as_drawable().render()
+    }
+
+    fn box_clone(&self) -> Box<dyn Drawable> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn Drawable) -> bool {
+        other.as_any().downcast_ref::<Self>() == Some(self)
+    }
+}
+
+impl From<Path> for Element {
+    fn from(x: Path) -> Element {
+        Element::Path(x)
+    }
+}
+
+impl From<Area> for Element {
+    fn from(x: Area) -> Element {
+        Element::Area(x)
+    }
+}
+
+impl From<Circle> for Element {
+    fn from(x: Circle) -> Element {
+        Element::Circle(x)
+    }
+}
+
+impl From<Line> for Element {
+    fn from(x: Line) -> Element {
+        Element::Line(x)
+    }
+}
+
+impl From<Label> for Element {
+    fn from(x: Label) -> Element {
+        Element::Label(x)
+    }
+}
+
+impl From<Tree> for Element {
+    fn from(x: Tree) -> Element {
+        Element::Tree(x)
+    }
+}
+
+impl From<World> for Element {
+    fn from(x: World) -> Element {
+        Element::World(x)
+    }
+}
+
+impl From<Box<dyn Drawable>> for Element {
+    fn from(x: Box<dyn Drawable>) -> Element {
+        Element::Custom(x)
+    }
+}
+
+impl Element {
+    /// Render to SVG with the path data of paths and areas, also in worlds,
+    /// written in `path_format` and rounded to `precision` decimal places.
+    pub fn into_svg(self, path_format: PathFormat, precision: Option<usize>) -> XMLElement {
+        match self {
+            Element::Path(x) => x.into_svg(path_format, precision),
+            Element::Area(x) => x.into_svg(path_format, precision),
+            Element::World(x) => x.into_svg(path_format, precision),
+            e => e.into(),
+        }
+    }
+}
+
+impl From<Element> for XMLElement {
+    fn from(e: Element) -> XMLElement {
+        match e {
+            Element::Path(x) => x.into(),
+            Element::Area(x) => x.into(),
+            Element::Circle(x) => x.into(),
+            Element::Line(x) => x.into(),
+            Element::Label(x) => x.into(),
+            Element::Tree(x) => x.into(),
+            Element::World(x) => x.into(),
+            Element::Custom(x) => x.render(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Area, Circle, Color, Frame, Point, Label};
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::drawable::Drawable;
+    use crate::element::Element;
+    use crate::graphic::{Path, World};
+    use crate::matrix3::Matrix3;
+    use crate::vector2::Vector2;
+
+    fn plan() -> Element {
+        let mut area = Area::new("garden", vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)])
+            .world(Point::new(1.0, 1.0), None);
+        area.add(Circle::new(2.0, 2.0, 1.0, Color::Black, Color::None));
+        let mut world = World::new("site", Point::new(0.0, 0.0));
+        world.add(area);
+        world.add(Label::new(Point::new(3.0, 3.0), "hello"));
+        world.into()
+    }
+
+    #[test]
+    fn walk() {
+        let plan = plan();
+        let kinds: Vec<&str> = plan.walk().map(|e| match e {
+            Element::World(_) => "world",
+            Element::Area(_) => "area",
+            Element::Circle(_) => "circle",
+            Element::Label(_) => "label",
+            _ => "other",
+        }).collect();
+        assert_eq!(vec!["world", "area", "circle", "label"], kinds);
+    }
+
+    #[test]
+    fn find() {
+        let mut plan = plan();
+        assert!(plan.find("garden").is_some());
+        assert!(plan.find_id("area-garden").is_some());
+        assert!(plan.find("pond").is_none());
+        if let Some(Element::Area(a)) = plan.find_mut("garden") {
+            a.fill = Color::Green;
+        }
+        match plan.find("garden") {
+            Some(Element::Area(a)) => assert_eq!(Color::Green, a.fill),
+            _ => panic!("garden not found"),
+        }
+    }
+
+    #[test]
+    fn find_placed() {
+        let mut world = World::new("site", Frame::new(10.0, 0.0).rotate(90.0));
+        world.add(Area::new("lawn", vec![(0.0, 0.0), (4.0, 0.0), (4.0, 2.0), (0.0, 2.0)]));
+        let plan: Element = world.into();
+        match plan.find_placed("lawn") {
+            Some(Element::Area(a)) => {
+                assert_approx_eq!(a.corners[2].x, 8.0);
+                assert_approx_eq!(a.corners[2].y, 4.0);
+                assert_approx_eq!(a.surface(), 8.0);
+                let c = a.centroid().unwrap();
+                assert_approx_eq!(c.x, 9.0);
+                assert_approx_eq!(c.y, 2.0);
+            }
+            _ => panic!("lawn not found"),
+        }
+        assert!(plan.find_placed("pond").is_none());
+    }
+
+    #[test]
+    fn nested_frames() {
+        let mut room = World::new("room", Frame::new(2.0, 1.0).flip_x()).show_axis(false);
+        room.add(Path::new("table", vec![(1.0, 0.0), (1.0, 2.0)]));
+        let mut building = Area::new("building", vec![(0.0, 0.0), (6.0, 0.0), (6.0, 4.0), (0.0, 4.0)])
+            .world(Frame::new(5.0, 0.0).rotate(90.0), None);
+        building.add(room);
+        let mut site = World::new("site", Point::new(10.0, 10.0));
+        site.add(building);
+        let site: Element = site.into();
+        let frames = site.frames("table").unwrap();
+        assert_eq!(3, frames.len());
+        assert_eq!(Some(frames[2]), site.find("room").and_then(|r| r.child_frame()));
+        assert_eq!(Some(vec![]), site.frames("site"));
+        assert!(site.frames("pond").is_none());
+        // (1, 0) in the room is (1, 1) in the building, (4, 1) in the area and (14, 11) on the site
+        let table = Point::new(1.0, 0.0).transform(&Frame::chain(&frames));
+        assert_approx_eq!(table.x, 14.0);
+        assert_approx_eq!(table.y, 11.0);
+        match site.find_placed("table") {
+            Some(Element::Path(p)) => {
+                assert_approx_eq!(p.points[0].x, 14.0);
+                assert_approx_eq!(p.points[0].y, 11.0);
+            }
+            _ => panic!("table not found"),
+        }
+        // only the room hides its axis
+        let svg = site.render().to_string();
+        assert_eq!(2 * 7, svg.matches("stroke=\"grey\"").count());
+    }
+
+    #[test]
+    fn hit() {
+        let matrix = Matrix3::unit();
+        let lawn: Element = Area::new("lawn", vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)])
+            .hole(vec![(4.0, 4.0), (6.0, 4.0), (6.0, 6.0), (4.0, 6.0)]).into();
+        assert!(lawn.hit(Point::new(1.0, 1.0), &matrix, 1.0));
+        assert!(!lawn.hit(Point::new(5.0, 5.0), &matrix, 1.0));
+        let edge: Element = Path::new("edge", vec![(0.0, 0.0), (10.0, 0.0)]).into();
+        assert!(edge.hit(Point::new(5.0, 0.4), &matrix, 1.0));
+        assert!(!edge.hit(Point::new(5.0, 0.6), &matrix, 1.0));
+        assert!(edge.hit(Point::new(5.0, 0.6), &Matrix3::scale(2.0, 2.0), 1.0));
+        let circle: Element = Circle::new(1.0, 1.0, 1.0, Color::Black, Color::None).into();
+        assert!(circle.hit(Point::new(1.5, 1.5), &matrix, 1.0));
+        assert!(!circle.hit(Point::new(2.0, 2.0), &matrix, 1.0));
+        let label: Element = Label::new(Point::new(0.0, 0.0), "label").into();
+        assert!(!label.hit(Point::new(0.0, 0.0), &matrix, 1.0));
+    }
+
+    #[test]
+    fn world_bounding_box() {
+        let mut world = World::new("site", Frame::new(100.0, 50.0).rotate(90.0)).axis_scale(1.0);
+        world.add(Circle::new(10.0, 0.0, 1.0, Color::Black, Color::None));
+        let b = world.bounding_box().unwrap();
+        assert_approx_eq!(b.max_y, 61.0);
+        assert_approx_eq!(b.min_y, 49.9);
+        assert_approx_eq!(b.min_x, 98.9);
+        assert_approx_eq!(b.max_x, 101.0);
+    }
+
+    #[test]
+    fn transform_world() {
+        let world = World::new("site", Frame::new(1.0, 2.0).rotate(30.0).flip_x());
+        let matrix = Matrix3::builder().flip_y(true).rotate(45.0).scale(2.0, 2.0)
+            .translate(Vector2::new(5.0, -3.0)).build();
+        let mut moved = world.clone();
+        moved.transform(&matrix);
+        for &(x, y) in &[(0.0, 0.0), (3.0, 4.0), (-2.0, 7.0)] {
+            let expected = Point::new(x, y).transform(&(world.matrix() * matrix));
+            let actual = Point::new(x, y).transform(&moved.matrix());
+            assert_approx_eq!(expected.x, actual.x);
+            assert_approx_eq!(expected.y, actual.y);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
+use crate::Point;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// Malformed input, like a plan or GeoJSON file.
+    Parse(String),
+    /// An element has fewer points than its shape needs.
+    TooFewPoints { element: String, found: usize, required: usize },
+    /// An element has a NaN or infinite value.
+    NotFinite { element: String, field: String },
+    /// An element has a negative size.
+    Negative { element: String, field: String },
+    /// A setting is zero, negative or too large to be used, like a print scale of 0.
+    OutOfRange { element: String, field: String },
+    /// A single path was asked for where there are several subpaths.
+    TooManySubpaths { element: String, found: usize },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse(message) => write!(f, "{}", message),
+            Error::TooFewPoints { element, found, required } =>
+                write!(f, "{} has {} points, at least {} are needed", element, found, required),
+            Error::NotFinite { element, field } => write!(f, "{} has a non-finite {}", element, field),
+            Error::Negative { element, field } => write!(f, "{} has a negative {}", element, field),
+            Error::OutOfRange { element, field } => write!(f, "{} has an out of range {}", element, field),
+            Error::TooManySubpaths { element, found } => write!(f, "{} has {} subpaths, 1 is allowed", element, found),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+pub(crate) fn check_finite(element: &str, field: &str, value: f64) -> Result<()> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(Error::NotFinite { element: element.to_string(), field: field.to_string() })
+    }
+}
+
+pub(crate) fn check_size(element: &str, field: &str, value: f64) -> Result<()> {
+    check_finite(element, field, value)?;
+    if value < 0.0 {
+        Err(Error::Negative { element: element.to_string(), field: field.to_string() })
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that `value` is finite and larger than 0.
+pub(crate) fn check_positive(element: &str, field: &str, value: f64) -> Result<()> {
+    check_finite(element, field, value)?;
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(Error::OutOfRange { element: element.to_string(), field: field.to_string() })
+    }
+}
+
+pub(crate) fn check_coordinate(element: &str, field: &str, c: &Point) -> Result<()> {
+    check_finite(element, field, c.x)?;
+    check_finite(element, field, c.y)
+}
+
+pub(crate) fn check_points(element: &str, points: &[Point], required: usize) -> Result<()> {
+    if points.len() < required {
+        return Err(Error::TooFewPoints { element: element.to_string(), found: points.len(), required });
+    }
+    for (i, p) in points.iter().enumerate() {
+        check_coordinate(element, &format!("point {}", i), p)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use simple_xml_serialize::XMLElement;
+
+    use crate::{Area, Circle, Color, Document, Error};
+    use crate::point::Points;
+    use crate::graphic::{Path, World};
+
+    #[test]
+    fn degenerate() {
+        match Path::try_new("hedge", Vec::<(f64, f64)>::new()) {
+            Err(Error::TooFewPoints { element, found: 0, required: 2 }) => assert_eq!("path 'hedge'", element),
+            r => panic!("unexpected {:?}", r),
+        }
+        assert!(Area::try_new("plot", vec![(0.0, 0.0), (1.0, 0.0), (1.0, f64::NAN)]).is_err());
+        assert!(Circle::try_new(0.0, 0.0, -1.0, Color::Black, Color::None).is_err());
+    }
+
+    #[test]
+    fn nested() {
+        let mut world = World::new("orchard", (0.0, 0.0));
+        world.add(Path::new("row", vec![(0.0, 0.0), (f64::INFINITY, 1.0)]));
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        doc.add(world);
+        assert_eq!("path 'row' has a non-finite point 1", doc.validate().unwrap_err().to_string());
+    }
+
+    #[test]
+    fn empty_does_not_panic() {
+        let _: XMLElement = Path::new("empty", Vec::<(f64, f64)>::new()).into();
+        let _: XMLElement = Area::new("empty", Vec::<(f64, f64)>::new()).into();
+        assert_eq!(0.0, Points::axis_scale(&[]));
+    }
+}
@@ -0,0 +1,146 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+use crate::matrix3::Matrix3;
+use crate::point::Point;
+use crate::vector2::Vector2;
+
+/// Where a coordinate system is placed in its reference: scaled, flipped and
+/// rotated, in that order, around its origin, then moved to `origin`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    pub origin: Point,
+    /// Counterclockwise, in degrees.
+    pub rotation: f64,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub scale_x: f64,
+    pub scale_y: f64,
+}
+
+impl Frame {
+    pub const fn new(x: f64, y: f64) -> Frame {
+        Frame::at(Point::new(x, y))
+    }
+
+    pub const fn at(origin: Point) -> Frame {
+        Frame { origin, rotation: 0.0, flip_x: false, flip_y: false, scale_x: 1.0, scale_y: 1.0 }
+    }
+
+    /// The frame placed by `matrix`; shear is not representable and dropped.
+    pub fn from_matrix(matrix: Matrix3) -> Frame {
+        let d = matrix.decompose();
+        Frame {
+            origin: Point::from(d.translate),
+            rotation: d.rotate,
+            flip_x: false,
+            flip_y: d.flip_y,
+            scale_x: d.scale_x,
+            scale_y: d.scale_y,
+        }
+    }
+
+    pub fn flip_x(self) -> Frame {
+        Frame { flip_x: !self.flip_x, ..self }
+    }
+
+    pub fn flip_x_if(self, v: bool) -> Frame {
+        Frame { flip_x: v, ..self }
+    }
+
+    pub fn flip_y(self) -> Frame {
+        Frame { flip_y: !self.flip_y, ..self }
+    }
+
+    pub fn flip_y_if(self, v: bool) -> Frame {
+        Frame { flip_y: v, ..self }
+    }
+
+    pub fn rotate(self, r: f64) -> Frame {
+        Frame { rotation: self.rotation + r, ..self }
+    }
+
+    pub fn scale_x(self, sx: f64) -> Frame {
+        Frame { scale_x: sx, ..self }
+    }
+
+    pub fn scale_y(self, sy: f64) -> Frame {
+        Frame { scale_y: sy, ..self }
+    }
+
+    pub fn translate(self, x: f64, y: f64) -> Frame {
+        Frame { origin: self.origin.translate(x, y), ..self }
+    }
+
+    /// The matrix mapping coordinates in the frame to its reference.
+    pub fn matrix(&self) -> Matrix3 {
+        Matrix3::builder()
+            .scale(self.scale_x, self.scale_y)
+            .flip_x(self.flip_x)
+            .flip_y(self.flip_y)
+            .rotate(self.rotation)
+            .translate(Vector2::from(self.origin))
+            .build()
+    }
+
+    /// The frame moved by `matrix`, composing its rotation, reflection and
+    /// scale with those of the frame; shear is not representable.
+    pub fn transform(self, matrix: &Matrix3) -> Frame {
+        Frame::from_matrix(self.matrix() * *matrix)
+    }
+
+    /// The matrix mapping coordinates in the innermost of nested frames to
+    /// the outermost reference; `frames[0]` places the outermost frame in
+    /// the reference, every next frame in the previous one.
+    pub fn chain(frames: &[Frame]) -> Matrix3 {
+        frames.iter().rev().fold(Matrix3::unit(), |m, f| m * f.matrix())
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Frame {
+        Frame::new(0.0, 0.0)
+    }
+}
+
+impl From<Point> for Frame {
+    fn from(origin: Point) -> Frame {
+        Frame::at(origin)
+    }
+}
+
+impl From<(f64, f64)> for Frame {
+    fn from(t: (f64, f64)) -> Frame {
+        Frame::new(t.0, t.1)
+    }
+}
+
+impl From<Vector2> for Frame {
+    fn from(v: Vector2) -> Frame {
+        Frame::new(v.x, v.y)
+    }
+}
+
+impl From<Frame> for Matrix3 {
+    fn from(frame: Frame) -> Matrix3 {
+        frame.matrix()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::frame::Frame;
+    use crate::matrix3::Matrix3;
+    use crate::point::Point;
+    use crate::vector2::Vector2;
+
+    #[test]
+    fn matrix() {
+        let frame = Frame::new(3.0, 4.0).rotate(30.0).flip_x().scale_x(2.0).scale_y(0.5);
+        let back = Frame::from_matrix(frame.matrix());
+        back.matrix().assert_approx_eq(frame.matrix());
+        Matrix3::from(Frame::from(Point::new(1.0, 2.0))).assert_approx_eq(Matrix3::translate(Vector2::new(1.0, 2.0)));
+        let moved = frame.transform(&Matrix3::translate(Vector2::new(1.0, 1.0)));
+        assert_eq!(Point::new(4.0, 5.0), moved.origin);
+        assert_eq!(Frame::new(1.0, 2.0), Frame::from(Vector2::new(1.0, 2.0)));
+    }
+}
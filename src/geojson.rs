@@ -0,0 +1,280 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! GeoJSON import and export of plan features.
+//!
+//! `Area` maps to Polygon, `Path` to LineString and `Tree` to Point. The
+//! `name` of every feature, and the tree data, are kept as feature properties.
+//! Exported coordinates are absolute plan coordinates, with all `World`
+//! transforms applied; curved edges are exported as straight segments.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use serde_json::{json, Map, Value};
+
+use crate::{Area, Point, Document};
+use crate::curve;
+use crate::drawable::Drawable;
+use crate::element::Element;
+use crate::error::{Error, Result};
+use crate::graphic::Path;
+use crate::matrix3::Matrix3;
+use crate::tree::Tree;
+
+fn position(c: &Point) -> Value {
+    json!([c.x, c.y])
+}
+
+fn feature(geometry: Value, properties: Value) -> Value {
+    json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+}
+
+fn collect(element: &Element, matrix: &Matrix3, features: &mut Vec<Value>) {
+    match element {
+        Element::Area(area) => {
+            let mut placed = area.clone();
+            placed.transform(matrix);
+            let rings: Vec<Value> = placed.rings(curve::TOLERANCE).iter().map(|r| {
+                let mut ring: Vec<Value> = r.iter().map(position).collect();
+                if let Some(first) = ring.first().cloned() {
+                    ring.push(first);
+                }
+                Value::Array(ring)
+            }).collect();
+            features.push(feature(json!({ "type": "Polygon", "coordinates": rings }),
+                                  json!({ "name": area.name })));
+            if let Some(world) = &area.world {
+                let matrix = world.matrix() * *matrix;
+                world.elements.iter().for_each(|e| collect(e, &matrix, features));
+            }
+        }
+        Element::Path(path) => {
+            let mut placed = path.clone();
+            placed.transform(matrix);
+            let line: Vec<Value> = placed.flatten(curve::TOLERANCE).iter().map(position).collect();
+            features.push(feature(json!({ "type": "LineString", "coordinates": line }),
+                                  json!({ "name": path.name })));
+        }
+        Element::Tree(tree) => {
+            let mut placed = tree.clone();
+            placed.transform(matrix);
+            features.push(feature(json!({ "type": "Point", "coordinates": position(&placed.location) }),
+                                  json!({
+                                      "name": placed.name,
+                                      "species": placed.species,
+                                      "trunk_diameter": placed.trunk_diameter,
+                                      "crown_diameter": placed.crown_diameter,
+                                  })));
+        }
+        Element::World(world) => {
+            let matrix = world.matrix() * *matrix;
+            world.elements.iter().for_each(|e| collect(e, &matrix, features));
+        }
+        Element::Circle(_) | Element::Line(_) | Element::Label(_) | Element::Custom(_) => (),
+    }
+}
+
+/// The Areas, Paths and Trees of the document as a GeoJSON FeatureCollection.
+pub fn to_geojson(document: &Document) -> Value {
+    let mut features = vec![];
+    for child in document.children() {
+        collect(child, &Matrix3::unit(), &mut features);
+    }
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+fn invalid<T: Into<String>>(message: T) -> Error {
+    Error::Parse(message.into())
+}
+
+fn coordinate(value: &Value) -> Result<Point> {
+    match value.as_array().map(|a| a.as_slice()) {
+        Some([x, y, ..]) => match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => Ok(Point::new(x, y)),
+            _ => Err(invalid(format!("invalid position {}", value))),
+        },
+        _ => Err(invalid(format!("invalid position {}", value))),
+    }
+}
+
+fn coordinates(value: &Value) -> Result<Vec<Point>> {
+    value.as_array()
+        .ok_or_else(|| invalid(format!("invalid coordinates {}", value)))?
+        .iter().map(coordinate).collect()
+}
+
+/// The positions of a linear ring, without the closing position.
+fn ring(value: &Value) -> Result<Vec<Point>> {
+    let mut corners = coordinates(value)?;
+    if corners.len() > 1 && corners.first() == corners.last() {
+        corners.pop();
+    }
+    Ok(corners)
+}
+
+fn polygon(name: &str, rings: &Value) -> Result<Area> {
+    let rings = rings.as_array().filter(|r| !r.is_empty())
+        .ok_or_else(|| invalid(format!("polygon {} has no rings", name)))?;
+    let mut area = Area::new(name, ring(&rings[0])?);
+    for hole in &rings[1..] {
+        area = area.hole(ring(hole)?);
+    }
+    area.validate()?;
+    Ok(area)
+}
+
+fn number(properties: &Map<String, Value>, key: &str) -> Option<f64> {
+    properties.get(key).and_then(|v| v.as_f64())
+}
+
+fn feature_elements(feature: &Value, elements: &mut Vec<Element>) -> Result<()> {
+    let empty = Map::new();
+    let properties = feature.get("properties").and_then(|p| p.as_object()).unwrap_or(&empty);
+    let name = properties.get("name").and_then(|n| n.as_str()).unwrap_or("");
+    let geometry = match feature.get("geometry") {
+        Some(Value::Null) | None => return Ok(()),
+        Some(g) => g,
+    };
+    let kind = geometry.get("type").and_then(|t| t.as_str()).unwrap_or("");
+    let coords = geometry.get("coordinates").unwrap_or(&Value::Null);
+    match kind {
+        "Polygon" => elements.push(polygon(name, coords)?.into()),
+        "MultiPolygon" => {
+            for p in coords.as_array().ok_or_else(|| invalid("invalid MultiPolygon"))? {
+                elements.push(polygon(name, p)?.into());
+            }
+        }
+        "LineString" => elements.push(Path::try_new(name, coordinates(coords)?)?.into()),
+        "MultiLineString" => {
+            for l in coords.as_array().ok_or_else(|| invalid("invalid MultiLineString"))? {
+                elements.push(Path::try_new(name, coordinates(l)?)?.into());
+            }
+        }
+        "Point" => {
+            let location = coordinate(coords)?;
+            let tree = Tree {
+                name: name.to_string(),
+                species: properties.get("species").and_then(|s| s.as_str()).unwrap_or("").to_string(),
+                trunk_diameter: number(properties, "trunk_diameter").unwrap_or(0.0),
+                crown_diameter: number(properties, "crown_diameter"),
+                location,
+                label_location: location,
+            };
+            tree.validate()?;
+            elements.push(tree.into())
+        }
+        _ => return Err(invalid(format!("unsupported geometry type '{}'", kind))),
+    }
+    Ok(())
+}
+
+/// Elements for all features of a GeoJSON FeatureCollection or Feature.
+///
+/// Features without geometry are skipped; degenerate geometry, like a
+/// polygon with fewer than 3 corners, is an error.
+pub fn from_geojson(value: &Value) -> Result<Vec<Element>> {
+    let mut elements = vec![];
+    match value.get("type").and_then(|t| t.as_str()) {
+        Some("FeatureCollection") => {
+            let features = value.get("features").and_then(|f| f.as_array())
+                .ok_or_else(|| invalid("FeatureCollection without features"))?;
+            for f in features {
+                feature_elements(f, &mut elements)?;
+            }
+        }
+        Some("Feature") => feature_elements(value, &mut elements)?,
+        _ => return Err(invalid("expected a FeatureCollection or Feature")),
+    }
+    Ok(elements)
+}
+
+pub fn write_geojson<W: Write>(document: &Document, out: W) -> io::Result<()> {
+    serde_json::to_writer_pretty(out, &to_geojson(document))?;
+    Ok(())
+}
+
+pub fn read_geojson<R: Read>(input: R) -> Result<Vec<Element>> {
+    let value: Value = serde_json::from_reader(input)
+        .map_err(|e| if e.is_io() { Error::Io(e.into()) } else { invalid(e.to_string()) })?;
+    from_geojson(&value)
+}
+
+impl Document {
+    /// Write the Areas, Paths and Trees of the document as GeoJSON to `filename`.
+    pub fn save_geojson(&self, filename: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(filename)?);
+        write_geojson(self, &mut out)?;
+        out.flush()
+    }
+
+    /// Add all features of the GeoJSON file `filename` to the document.
+    pub fn load_geojson(&mut self, filename: &str) -> Result<()> {
+        let elements = read_geojson(BufReader::new(File::open(filename)?))?;
+        elements.into_iter().for_each(|e| self.add(e));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Area, Point, Document, Element};
+    use crate::geojson;
+    use crate::graphic::{Path, World};
+    use crate::tree::Tree;
+
+    fn tree() -> Tree {
+        Tree {
+            name: "oak".into(),
+            species: "Quercus robur".into(),
+            trunk_diameter: 0.5,
+            crown_diameter: Some(8.0),
+            location: Point::new(3.0, 4.0),
+            label_location: Point::new(3.0, 4.0),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut doc = Document::new(0.0, 0.0, 40.0, 30.0, 10);
+        doc.add(Area::new("plot", vec![(0.0, 0.0), (40.0, 0.0), (40.0, 30.0)])
+            .hole(vec![(30.0, 5.0), (35.0, 5.0), (35.0, 10.0)]));
+        doc.add(Path::new("hedge", vec![(0.0, 1.0), (20.0, 1.0)]));
+        doc.add(tree());
+        let elements = geojson::from_geojson(&geojson::to_geojson(&doc)).unwrap();
+        assert_eq!(doc.children(), &elements[..]);
+    }
+
+    #[test]
+    fn world_transform() {
+        let mut doc = Document::new(0.0, 0.0, 40.0, 30.0, 10);
+        let mut world = World::new("orchard", Point::new(10.0, 20.0));
+        world.add(tree());
+        doc.add(world);
+        let elements = geojson::from_geojson(&geojson::to_geojson(&doc)).unwrap();
+        match &elements[..] {
+            [Element::Tree(t)] => assert_eq!(Point::new(13.0, 24.0), t.location),
+            _ => panic!("expected one tree"),
+        }
+    }
+
+    #[test]
+    fn unsupported() {
+        let value = serde_json::json!({
+            "type": "Feature",
+            "geometry": { "type": "GeometryCollection", "geometries": [] },
+            "properties": null,
+        });
+        assert!(geojson::from_geojson(&value).is_err());
+    }
+
+    #[test]
+    fn degenerate() {
+        let value = serde_json::json!({
+            "type": "Feature",
+            "geometry": { "type": "LineString", "coordinates": [[1.0, 2.0]] },
+            "properties": { "name": "stub" },
+        });
+        let e = geojson::from_geojson(&value).unwrap_err();
+        assert_eq!("path 'stub' has 1 points, at least 2 are needed", e.to_string());
+    }
+}
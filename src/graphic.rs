@@ -2,17 +2,73 @@
 
 use simple_xml_serialize::XMLElement;
 
-use std::fmt::Write as FmtWrite;
+use std::any::Any;
 use std::iter::IntoIterator;
 
+use crate::boolean::{self, Operation};
+use crate::bounding_box::BoundingBox;
 use crate::color::Color;
-use crate::Coordinate;
-use crate::coordinate::Coordinates;
+use crate::Point;
+use crate::point::Points;
+use crate::drawable::{self, Drawable};
+use crate::element::Element;
+use crate::error::{self, Error, Result};
+use crate::frame::Frame;
+use crate::matrix3::Matrix3;
+use crate::curve::{self, Curve};
+use crate::offset::{self, Join};
+use crate::path_data::{self, PathFormat, Segment};
+use crate::polygon::{self, Winding};
 use crate::text::Title;
+use crate::vector2::Vector2;
+
+/// The outline through `points` as path segments, see `curve::flatten` for `curves`.
+fn segments(points: &[Point], curves: &[Option<Curve>], close: bool) -> Vec<Segment> {
+    let curve = |i: usize| curves.get(i).copied().flatten();
+    let mut segments: Vec<Segment> = points.iter().enumerate().map(|(i, &c)| match (i, curve(i)) {
+        (0, _) => Segment::Move(c),
+        (_, Some(curve)) => Segment::Curve(curve, c),
+        (_, None) => Segment::Line(c),
+    }).collect();
+    if close && !points.is_empty() {
+        if let (Some(curve), true) = (curve(0), points.len() > 1) {
+            segments.push(Segment::Curve(curve, points[0]));
+        }
+        segments.push(Segment::Close);
+    }
+    segments
+}
+
+/// Set the curve arriving at point `index`.
+fn set_curve(curves: &mut Vec<Option<Curve>>, index: usize, curve: Curve) {
+    if curves.len() <= index {
+        curves.resize(index + 1, None);
+    }
+    curves[index] = Some(curve);
+}
+
+/// Name for part `i` of `count`, numbered `name-1`, `name-2`... when there is more than one.
+fn numbered<U: ToString>(name: &U, i: usize, count: usize) -> String {
+    if count == 1 { name.to_string() } else { format!("{}-{}", name.to_string(), i + 1) }
+}
+
+fn validate_curves(element: &str, curves: &[Option<Curve>]) -> Result<()> {
+    curves.iter().flatten().try_for_each(|c| c.validate(element))
+}
+
+/// Check a closed outline: it needs 3 corners, or 2 when one of its edges is
+/// curved, like a half circle on a straight edge.
+fn validate_outline(element: &str, corners: &[Point], curves: &[Option<Curve>]) -> Result<()> {
+    let curved = curves.iter().take(corners.len()).any(Option::is_some);
+    error::check_points(element, corners, if curved { 2 } else { 3 })?;
+    validate_curves(element, curves)
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Path {
-    pub points: Vec<Coordinate>,
+    pub points: Vec<Point>,
+    /// `curves[i]` is the edge arriving at `points[i]`, straight when it is `None` or missing.
+    pub curves: Vec<Option<Curve>>,
     pub color: Color,
     pub stroke_width: Option<f64>,
     pub stroke_dash: Option<String>,
@@ -21,9 +77,10 @@ pub struct Path {
 
 
 impl Path {
-    pub fn new<T: Into<Coordinate>, U: ToString>(name: U, points: Vec<T>) -> Self {
+    pub fn new<T: Into<Point>, U: ToString>(name: U, points: Vec<T>) -> Self {
         Self {
             points: points.into_iter().map(|p| p.into()).collect(),
+            curves: vec![],
             color: Color::Black,
             stroke_width: None,
             stroke_dash: None,
@@ -31,6 +88,59 @@ impl Path {
         }
     }
 
+    /// Like `new`, but rejects paths with fewer than 2 points or non-finite points.
+    pub fn try_new<T: Into<Point>, U: ToString>(name: U, points: Vec<T>) -> Result<Self> {
+        let path = Path::new(name, points);
+        path.validate()?;
+        Ok(path)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let element = format!("path '{}'", self.name);
+        error::check_points(&element, &self.points, 2)?;
+        validate_curves(&element, &self.curves)?;
+        if let Some(w) = self.stroke_width {
+            error::check_size(&element, "stroke width", w)?;
+        }
+        Ok(())
+    }
+
+    /// Make the edge arriving at point `index` a curve.
+    pub fn curve(mut self, index: usize, curve: Curve) -> Self {
+        set_curve(&mut self.curves, index, curve);
+        self
+    }
+
+    pub fn segments(&self) -> Vec<Segment> {
+        segments(&self.points, &self.curves, false)
+    }
+
+    /// The points with every curve replaced by straight lines within `tolerance`.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Point> {
+        curve::flatten(&self.points, &self.curves, false, tolerance)
+    }
+
+    /// Length along the path, curves included.
+    pub fn length(&self) -> f64 {
+        polygon::length(&self.flatten(curve::TOLERANCE))
+    }
+
+    /// Distance from `point` to the path, curves included.
+    pub fn distance(&self, point: Point) -> f64 {
+        polygon::polyline_distance(&self.flatten(curve::TOLERANCE), point)
+    }
+
+    /// The areas within `distance` of the path, in the color of the path and
+    /// numbered like `PathBuilder::areas` when there is more than one.
+    pub fn buffer(&self, distance: f64, join: Join) -> Vec<Area> {
+        let polygons = offset::polyline(&self.flatten(curve::TOLERANCE), distance, join);
+        let count = polygons.len();
+        polygons.into_iter().enumerate().map(|(i, mut rings)| {
+            let holes = rings.split_off(1).into_iter().map(Ring::new).collect();
+            Area { holes, ..Area::new(numbered(&self.name, i, count), rings.remove(0)).color(self.color) }
+        }).collect()
+    }
+
     pub fn color(self, color: Color) -> Self {
         Self { color, ..self }
     }
@@ -44,14 +154,11 @@ impl Path {
     }
 }
 
-impl Into<XMLElement> for Path {
-    fn into(self) -> XMLElement {
-        let mut data = String::new();
-        write!(&mut data, "M{},{} ", self.points[0].x, self.points[0].y).unwrap();
-        self.points.iter().skip(1).for_each(|c| {
-            write!(&mut data, "L{},{} ", c.x, c.y).unwrap();
-        });
-
+impl Path {
+    /// Render to SVG with path data written in `path_format`, rounded to
+    /// `precision` decimal places.
+    pub fn into_svg(self, path_format: PathFormat, precision: Option<usize>) -> XMLElement {
+        let data = path_data::format(&self.segments(), path_format, precision);
         XMLElement::new("path")
             .attr("id", format!("area-{}", self.name))
             .attr("d", data)
@@ -63,45 +170,334 @@ impl Into<XMLElement> for Path {
     }
 }
 
-pub struct PathBuilder {
+impl Into<XMLElement> for Path {
+    fn into(self) -> XMLElement {
+        self.into_svg(PathFormat::new(), None)
+    }
+}
+
+impl Drawable for Path {
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_points(self.flatten(curve::TOLERANCE).iter().map(|p| p.tup()))
+    }
+
+    fn transform(&mut self, matrix: &Matrix3) {
+        self.points.iter_mut().for_each(|p| *p = p.transform(matrix));
+        self.curves.iter_mut().flatten().for_each(|c| *c = c.transform(matrix));
+    }
+
+    fn render(&self) -> XMLElement {
+        self.clone().into()
+    }
+
+    fn box_clone(&self) -> Box<dyn Drawable> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn Drawable) -> bool {
+        other.as_any().downcast_ref::<Self>() == Some(self)
+    }
+}
+
+/// One connected run of points of a `PathBuilder`.
+#[derive(Debug, Clone)]
+struct Subpath {
     points: Vec<(f64, f64)>,
+    /// `curves[i]` is the edge arriving at `points[i]`.
+    curves: Vec<Option<Curve>>,
+    closed: bool,
+}
+
+impl Subpath {
+    fn new(start: (f64, f64)) -> Subpath {
+        Subpath { points: vec![start], curves: vec![None], closed: false }
+    }
+
+    fn curves(&self) -> Vec<Option<Curve>> {
+        if self.curves.iter().all(|c| c.is_none()) { vec![] } else { self.curves.clone() }
+    }
+
+    fn path(&self, name: String) -> Path {
+        Path { curves: self.curves(), ..Path::new(name, self.points.clone()) }
+    }
+
+    /// When the last point is back at the start it is dropped, and its edge
+    /// closes the outline.
+    fn ring(&self) -> Ring {
+        let mut ring = self.clone();
+        if ring.points.len() > 1 && ring.points.first() == ring.points.last() {
+            ring.points.pop();
+            ring.curves[0] = ring.curves.pop().flatten();
+        }
+        Ring { curves: ring.curves(), ..Ring::new(ring.points) }
+    }
+
+    fn area(&self, name: String) -> Area {
+        let ring = self.ring();
+        Area { curves: ring.curves, ..Area::new(name, ring.corners) }
+    }
+}
+
+/// The last point of a subpath closer than this to its start is moved onto
+/// the start when closing, absorbing the rounding of polar steps.
+const CLOSE_TOLERANCE: f64 = 1e-9;
+
+/// Builds paths and area outlines step by step, like a turtle.
+///
+/// Steps are relative to the current point, except `to` and `move_to`.
+/// Angles are in degrees, counterclockwise from the x axis; a compass
+/// bearing `b` is heading `90 - b`.
+///
+/// ```
+/// # use jyd2d::graphic::PathBuilder;
+/// // a plot surveyed as bearings and distances
+/// let plot = PathBuilder::new(0.0, 0.0)
+///     .heading(0.0, 30.0)
+///     .turn(90.0).forward(20.0)
+///     .turn(90.0).forward(30.0)
+///     .close()
+///     .area("plot");
+/// assert_eq!(4, plot.corners.len());
+/// ```
+pub struct PathBuilder {
+    /// Never empty, the last one is the current subpath.
+    subpaths: Vec<Subpath>,
     last: (f64, f64),
+    /// Direction of travel in degrees.
+    heading: f64,
 }
 
 impl PathBuilder {
     pub fn new(x: f64, y: f64) -> PathBuilder {
-        PathBuilder { points: vec![(x, y)], last: (x, y) }
+        PathBuilder { subpaths: vec![Subpath::new((x, y))], last: (x, y), heading: 0.0 }
+    }
+
+    /// The points visited, without the curves between them; like `path`
+    /// this fails when there are several subpaths, use `paths` or `area` then.
+    pub fn done(self) -> Result<Vec<(f64, f64)>> {
+        match self.subpaths.len() {
+            1 => Ok(self.subpaths.into_iter().next().unwrap().points),
+            found => Err(Error::TooManySubpaths { element: "path builder".to_string(), found }),
+        }
     }
 
-    pub fn done(self) -> Vec<(f64, f64)> {
-        self.points
+    /// The subpath steps are added to; a step after `close` starts a new one.
+    fn current(&mut self) -> &mut Subpath {
+        if self.subpaths.last().map(|s| s.closed).unwrap_or(true) {
+            self.subpaths.push(Subpath::new(self.last));
+        }
+        self.subpaths.last_mut().unwrap()
     }
 
-    pub fn dx(mut self, dx: f64) -> PathBuilder {
-        let new = (self.last.0 + dx, self.last.1);
-        self.points.push(new);
+    fn step(mut self, dx: f64, dy: f64, curve: Option<Curve>) -> PathBuilder {
+        let new = (self.last.0 + dx, self.last.1 + dy);
+        let subpath = self.current();
+        subpath.points.push(new);
+        subpath.curves.push(curve);
+        if curve.is_none() && (dx != 0.0 || dy != 0.0) {
+            self.heading = dy.atan2(dx).to_degrees();
+        }
         self.last = new;
         self
     }
 
-    pub fn dy(mut self, dy: f64) -> PathBuilder {
-        let new = (self.last.0, self.last.1 + dy);
-        self.points.push(new);
-        self.last = new;
+    /// Set the heading to the direction of the curve where it arrives at `to`.
+    fn curve_step(mut self, curve: Curve, dx: f64, dy: f64) -> PathBuilder {
+        let from = Point::new(self.last.0, self.last.1);
+        let to = Point::new(from.x + dx, from.y + dy);
+        let before = match curve {
+            Curve::Quadratic(c) if c != to => c,
+            Curve::Cubic(_, c) if c != to => c,
+            _ => {
+                let points = curve.flatten(from, to, curve::TOLERANCE / 10.0);
+                if points.len() > 1 { points[points.len() - 2] } else { from }
+            }
+        };
+        if before != to {
+            self.heading = (to.y - before.y).atan2(to.x - before.x).to_degrees();
+        }
+        self.step(dx, dy, Some(curve))
+    }
+
+    pub fn dx(self, dx: f64) -> PathBuilder {
+        self.step(dx, 0.0, None)
+    }
+
+    pub fn dy(self, dy: f64) -> PathBuilder {
+        self.step(0.0, dy, None)
+    }
+
+    pub fn d(self, dx: f64, dy: f64) -> PathBuilder {
+        self.step(dx, dy, None)
+    }
+
+    /// Straight line to the absolute position `x`,`y`.
+    pub fn to(self, x: f64, y: f64) -> PathBuilder {
+        let (dx, dy) = (x - self.last.0, y - self.last.1);
+        self.step(dx, dy, None)
+    }
+
+    /// Start a new subpath at the absolute position `x`,`y`.
+    pub fn move_to(mut self, x: f64, y: f64) -> PathBuilder {
+        if self.subpaths.last().is_some_and(|s| s.points.len() == 1 && !s.closed) {
+            self.subpaths.pop();
+        }
+        self.subpaths.push(Subpath::new((x, y)));
+        self.last = (x, y);
         self
     }
 
-    pub fn d(mut self, dx: f64, dy: f64) -> PathBuilder {
-        let new = (self.last.0 + dx, self.last.1 + dy);
-        self.points.push(new);
-        self.last = new;
+    /// Turn to `angle` and go `length` in that direction.
+    pub fn heading(mut self, angle: f64, length: f64) -> PathBuilder {
+        self.heading = angle;
+        self.forward(length)
+    }
+
+    /// Change the heading by `angle`, counterclockwise.
+    pub fn turn(mut self, angle: f64) -> PathBuilder {
+        self.heading += angle;
+        self
+    }
+
+    /// Go `length` in the current heading.
+    pub fn forward(self, length: f64) -> PathBuilder {
+        let (sin, cos) = self.heading.to_radians().sin_cos();
+        let heading = self.heading;
+        let mut builder = self.step(length * cos, length * sin, None);
+        // keep the exact heading rather than the one recomputed from the step
+        builder.heading = heading;
+        builder
+    }
+
+    /// Go back to the start of the current subpath and close it.
+    pub fn close(mut self) -> PathBuilder {
+        let last = self.last;
+        let subpath = match self.subpaths.last_mut() {
+            Some(s) if !s.closed => s,
+            _ => return self,
+        };
+        let start = subpath.points[0];
+        let near = (last.0 - start.0).hypot(last.1 - start.1) <= CLOSE_TOLERANCE;
+        if subpath.points.len() > 1 && near {
+            *subpath.points.last_mut().unwrap() = start;
+        } else if subpath.points.len() > 1 {
+            subpath.points.push(start);
+            subpath.curves.push(None);
+        }
+        subpath.closed = true;
+        self.last = start;
         self
     }
+
+    /// Quadratic Bezier curve, the control point and end are relative to the current point.
+    pub fn quadratic(self, cdx: f64, cdy: f64, dx: f64, dy: f64) -> PathBuilder {
+        let control = Point::new(self.last.0 + cdx, self.last.1 + cdy);
+        self.curve_step(Curve::Quadratic(control), dx, dy)
+    }
+
+    /// Cubic Bezier curve, the control points and end are relative to the current point.
+    pub fn cubic(self, c1dx: f64, c1dy: f64, c2dx: f64, c2dy: f64, dx: f64, dy: f64) -> PathBuilder {
+        let c1 = Point::new(self.last.0 + c1dx, self.last.1 + c1dy);
+        let c2 = Point::new(self.last.0 + c2dx, self.last.1 + c2dy);
+        self.curve_step(Curve::Cubic(c1, c2), dx, dy)
+    }
+
+    /// Elliptical arc as in SVG `a`, ending `dx`,`dy` from the current point.
+    #[allow(clippy::too_many_arguments)]
+    pub fn arc(self, rx: f64, ry: f64, rotation: f64, large_arc: bool, sweep: bool, dx: f64, dy: f64) -> PathBuilder {
+        self.curve_step(Curve::Arc { rx, ry, rotation, large_arc, sweep }, dx, dy)
+    }
+
+    /// The path through the points visited; fails when `move_to` or a step
+    /// after `close` started more than one subpath, see `paths`.
+    pub fn path<U: ToString>(self, name: U) -> Result<Path> {
+        match self.subpaths.len() {
+            1 => Ok(self.subpaths[0].path(name.to_string())),
+            found => Err(Error::TooManySubpaths { element: format!("path '{}'", name.to_string()), found }),
+        }
+    }
+
+    /// A path for every subpath, numbered `name-1`, `name-2`... when there is more than one.
+    pub fn paths<U: ToString>(self, name: U) -> Vec<Path> {
+        let count = self.subpaths.len();
+        self.subpaths.iter().enumerate().map(|(i, s)| s.path(numbered(&name, i, count))).collect()
+    }
+
+    /// The first subpath as the outline of an area; every further subpath
+    /// becomes a hole in it. Use `areas` for separate areas.
+    pub fn area<U: ToString>(self, name: U) -> Area {
+        let area = self.subpaths[0].area(name.to_string());
+        Area { holes: self.subpaths[1..].iter().map(|s| s.ring()).collect(), ..area }
+    }
+
+    /// An area for every subpath, numbered like `paths`.
+    pub fn areas<U: ToString>(self, name: U) -> Vec<Area> {
+        let count = self.subpaths.len();
+        self.subpaths.iter().enumerate().map(|(i, s)| s.area(numbered(&name, i, count))).collect()
+    }
+}
+
+/// A closed outline, used for the holes of an `Area`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ring {
+    pub corners: Vec<Point>,
+    /// `curves[i]` is the edge arriving at `corners[i]`, straight when it is
+    /// `None` or missing; `curves[0]` is the edge closing the outline.
+    pub curves: Vec<Option<Curve>>,
+}
+
+impl Ring {
+    pub fn new<T: Into<Point>>(corners: Vec<T>) -> Ring {
+        Ring { corners: corners.into_iter().map(|c| c.into()).collect(), curves: vec![] }
+    }
+
+    /// Make the edge arriving at corner `index` a curve.
+    pub fn curve(mut self, index: usize, curve: Curve) -> Self {
+        set_curve(&mut self.curves, index, curve);
+        self
+    }
+
+    pub fn segments(&self) -> Vec<Segment> {
+        segments(&self.corners, &self.curves, true)
+    }
+
+    /// The corners with every curve replaced by straight lines within `tolerance`.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Point> {
+        curve::flatten(&self.corners, &self.curves, true, tolerance)
+    }
+
+    fn transform(&mut self, matrix: &Matrix3) {
+        self.corners.iter_mut().for_each(|c| *c = c.transform(matrix));
+        self.curves.iter_mut().flatten().for_each(|c| *c = c.transform(matrix));
+    }
+
+    fn validate(&self, element: &str) -> Result<()> {
+        validate_outline(element, &self.corners, &self.curves)
+    }
+}
+
+impl<T: Into<Point>> From<Vec<T>> for Ring {
+    fn from(corners: Vec<T>) -> Ring {
+        Ring::new(corners)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Area {
-    pub corners: Vec<Coordinate>,
+    pub corners: Vec<Point>,
+    /// `curves[i]` is the edge arriving at `corners[i]`, straight when it is
+    /// `None` or missing; `curves[0]` is the edge closing the outline.
+    pub curves: Vec<Option<Curve>>,
+    /// Parts cut out of the area, like a pond in a lawn.
+    pub holes: Vec<Ring>,
     pub color: Color,
     pub fill: Color,
     pub world: Option<World>,
@@ -110,9 +506,11 @@ pub struct Area {
 }
 
 impl Area {
-    pub fn new<T: Into<Coordinate>, U: ToString>(name: U, corners: Vec<T>) -> Area {
+    pub fn new<T: Into<Point>, U: ToString>(name: U, corners: Vec<T>) -> Area {
         Area {
             corners: corners.into_iter().map(|c| c.into()).collect(),
+            curves: vec![],
+            holes: vec![],
             color: Color::Black,
             fill: Color::None,
             world: None,
@@ -121,6 +519,169 @@ impl Area {
         }
     }
 
+    /// Like `new`, but rejects non-finite corners and areas with fewer than 3
+    /// corners, or 2 when an edge is curved.
+    pub fn try_new<T: Into<Point>, U: ToString>(name: U, corners: Vec<T>) -> Result<Area> {
+        let area = Area::new(name, corners);
+        area.validate()?;
+        Ok(area)
+    }
+
+    /// Check the corners of the area and everything in its world.
+    pub fn validate(&self) -> Result<()> {
+        let element = format!("area '{}'", self.name);
+        validate_outline(&element, &self.corners, &self.curves)?;
+        for (i, hole) in self.holes.iter().enumerate() {
+            hole.validate(&format!("hole {} of {}", i + 1, element))?;
+        }
+        if let Some(w) = self.stroke_width {
+            error::check_size(&element, "stroke width", w)?;
+        }
+        match &self.world {
+            Some(world) => world.validate(),
+            None => Ok(()),
+        }
+    }
+
+    /// Make the edge arriving at corner `index` a curve; index 0 is the
+    /// edge from the last corner back to the first.
+    pub fn curve(mut self, index: usize, curve: Curve) -> Self {
+        set_curve(&mut self.curves, index, curve);
+        self
+    }
+
+    /// Cut `hole` out of the area.
+    pub fn hole<R: Into<Ring>>(mut self, hole: R) -> Self {
+        self.holes.push(hole.into());
+        self
+    }
+
+    /// The outline followed by a subpath for every hole.
+    pub fn segments(&self) -> Vec<Segment> {
+        let mut segments = segments(&self.corners, &self.curves, true);
+        self.holes.iter().for_each(|h| segments.extend(h.segments()));
+        segments
+    }
+
+    /// The corners of the outline, without holes, with every curve replaced
+    /// by straight lines within `tolerance`.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Point> {
+        curve::flatten(&self.corners, &self.curves, true, tolerance)
+    }
+
+    /// The flattened outline followed by the flattened holes.
+    pub fn rings(&self, tolerance: f64) -> Vec<Vec<Point>> {
+        let mut rings = vec![self.flatten(tolerance)];
+        rings.extend(self.holes.iter().map(|h| h.flatten(tolerance)));
+        rings
+    }
+
+    /// Surface enclosed by the outline, ignoring the holes; positive when
+    /// the corners go counterclockwise.
+    pub fn signed_area(&self) -> f64 {
+        polygon::signed_area(&self.flatten(curve::TOLERANCE))
+    }
+
+    /// Surface of the area with the holes cut out, whatever the winding of
+    /// the outline and the holes.
+    pub fn surface(&self) -> f64 {
+        let rings = self.rings(curve::TOLERANCE);
+        let outline = polygon::signed_area(&rings[0]).abs();
+        rings[1..].iter().fold(outline, |s, hole| s - polygon::signed_area(hole).abs())
+    }
+
+    /// Length of the outline and the edges of all holes.
+    pub fn perimeter(&self) -> f64 {
+        self.rings(curve::TOLERANCE).iter().map(|r| polygon::perimeter(r)).sum()
+    }
+
+    /// Center of mass of the area with the holes cut out, `None` when it
+    /// has no surface.
+    pub fn centroid(&self) -> Option<Point> {
+        let mut total = 0.0;
+        let mut sum = Vector2::new(0.0, 0.0);
+        for (i, ring) in self.rings(curve::TOLERANCE).iter().enumerate() {
+            let area = polygon::signed_area(ring).abs() * if i == 0 { 1.0 } else { -1.0 };
+            if let Some(c) = polygon::centroid(ring) {
+                sum = sum + Vector2::from(&c) * area;
+                total += area;
+            }
+        }
+        if total <= 0.0 {
+            return None;
+        }
+        let c = sum / total;
+        Some(Point::new(c.x, c.y))
+    }
+
+    /// The direction in which the corners of the outline go around.
+    pub fn winding(&self) -> Winding {
+        polygon::winding(&self.flatten(curve::TOLERANCE))
+    }
+
+    /// Whether the area is convex: an outline without dents and no holes.
+    pub fn is_convex(&self) -> bool {
+        self.holes.is_empty() && polygon::is_convex(&self.flatten(curve::TOLERANCE))
+    }
+
+    /// Whether `point` is inside the outline and not in a hole.
+    pub fn contains(&self, point: Point) -> bool {
+        polygon::contains_rings(&self.rings(curve::TOLERANCE), point)
+    }
+
+    /// Combine the area with `other`, see `boolean::apply`.
+    ///
+    /// Curves are flattened. The results have the name and style of this
+    /// area, numbered `name-1`, `name-2`... when there is more than one, and
+    /// no world.
+    pub fn boolean(&self, operation: Operation, other: &Area) -> Vec<Area> {
+        self.derived(boolean::apply(operation, &self.rings(curve::TOLERANCE), &other.rings(curve::TOLERANCE)))
+    }
+
+    /// Areas with the name and style of this one for `polygons`.
+    fn derived(&self, polygons: Vec<Vec<Vec<Point>>>) -> Vec<Area> {
+        let count = polygons.len();
+        polygons.into_iter().enumerate().map(|(i, mut rings)| {
+            let holes = rings.split_off(1).into_iter().map(Ring::new).collect();
+            Area {
+                corners: rings.remove(0),
+                curves: vec![],
+                holes,
+                world: None,
+                name: numbered(&self.name, i, count),
+                ..self.clone()
+            }
+        }).collect()
+    }
+
+    pub fn union(&self, other: &Area) -> Vec<Area> {
+        self.boolean(Operation::Union, other)
+    }
+
+    pub fn intersection(&self, other: &Area) -> Vec<Area> {
+        self.boolean(Operation::Intersection, other)
+    }
+
+    /// The area with `other` cut out.
+    pub fn difference(&self, other: &Area) -> Vec<Area> {
+        self.boolean(Operation::Difference, other)
+    }
+
+    pub fn xor(&self, other: &Area) -> Vec<Area> {
+        self.boolean(Operation::Xor, other)
+    }
+
+    /// The area grown by `distance`, or shrunk when `distance` is negative;
+    /// holes shrink as the area grows. Named and styled like `boolean`.
+    pub fn offset(&self, distance: f64, join: Join) -> Vec<Area> {
+        self.derived(offset::polygon(&self.rings(curve::TOLERANCE), distance, join))
+    }
+
+    /// The area within `distance` of `center`, see `offset::point`.
+    pub fn around<U: ToString>(name: U, center: Point, distance: f64, join: Join) -> Area {
+        Area::new(name, offset::point(center, distance, join))
+    }
+
     pub fn color(self, color: Color) -> Self {
         Area { color, ..self }
     }
@@ -129,8 +690,8 @@ impl Area {
         Area { fill, ..self }
     }
 
-    pub fn world(self, origin: Coordinate, stroke_width: Option<f64>) -> Self {
-        let scale = Coordinates::axis_scale(&self.corners);
+    pub fn world<F: Into<Frame>>(self, origin: F, stroke_width: Option<f64>) -> Self {
+        let scale = Points::axis_scale(&self.corners);
         let world = World::new(format!("world-{}", self.name), origin).axis_scale(scale).stroke_width_opt(stroke_width);
         Area { world: Some(world), ..self }
     }
@@ -139,25 +700,21 @@ impl Area {
         Area { stroke_width: Some(stroke_width), ..self }
     }
 
-    pub fn add<X: Into<XMLElement>>(&mut self, x: X) {
+    pub fn add<X: Into<Element>>(&mut self, x: X) {
         self.world.as_mut().map(|w| w.add(x));
     }
 
     pub fn add_all<XN>(&mut self, xn: XN)
-        where XN: IntoIterator, XN::Item: Into<XMLElement> {
+        where XN: IntoIterator, XN::Item: Into<Element> {
         self.world.as_mut().map(|w| w.add_all(xn));
     }
 }
 
-impl Into<XMLElement> for Area {
-    fn into(self) -> XMLElement {
-        let mut data = String::new();
-        let corners = self.corners;
-        write!(&mut data, "M{},{} ", corners[0].x, corners[0].y).unwrap();
-        corners.iter().skip(1).for_each(|c| {
-            write!(&mut data, "L{},{} ", c.x, c.y).unwrap();
-        });
-        write!(&mut data, "z").unwrap();
+impl Area {
+    /// Render to SVG with path data written in `path_format`, rounded to
+    /// `precision` decimal places, also for the elements of its world.
+    pub fn into_svg(self, path_format: PathFormat, precision: Option<usize>) -> XMLElement {
+        let data = path_data::format(&self.segments(), path_format, precision);
         XMLElement::new("g")
             .element(
                 XMLElement::new("path")
@@ -165,11 +722,57 @@ impl Into<XMLElement> for Area {
                     //.attr("opacity", 0.5)
                     .attr("d", data)
                     .attr("fill", self.fill)
+                    .attr_opt("fill-rule", if self.holes.is_empty() { None } else { Some("evenodd") })
                     .attr("stroke", self.color)
                     .attr_opt("stroke-width", self.stroke_width)
                     .element(Title(self.name))
             )
-            .element_opt(self.world)
+            .element_opt(self.world.map(|w| w.into_svg(path_format, precision)))
+    }
+}
+
+impl Into<XMLElement> for Area {
+    fn into(self) -> XMLElement {
+        self.into_svg(PathFormat::new(), None)
+    }
+}
+
+impl Drawable for Area {
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let corners = BoundingBox::from_points(self.flatten(curve::TOLERANCE).iter().map(|c| c.tup()));
+        match self.world.as_ref().and_then(|w| w.bounding_box()) {
+            Some(b) => Some(BoundingBox::union_opt(corners, b)),
+            None => corners,
+        }
+    }
+
+    fn transform(&mut self, matrix: &Matrix3) {
+        self.corners.iter_mut().for_each(|c| *c = c.transform(matrix));
+        self.curves.iter_mut().flatten().for_each(|c| *c = c.transform(matrix));
+        self.holes.iter_mut().for_each(|h| h.transform(matrix));
+        if let Some(w) = self.world.as_mut() {
+            w.transform(matrix)
+        }
+    }
+
+    fn render(&self) -> XMLElement {
+        self.clone().into()
+    }
+
+    fn box_clone(&self) -> Box<dyn Drawable> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn Drawable) -> bool {
+        other.as_any().downcast_ref::<Self>() == Some(self)
     }
 }
 
@@ -186,6 +789,24 @@ impl Circle {
     pub fn new(cx: f64, cy: f64, r: f64, color: Color, fill: Color) -> Circle {
         Circle { cx, cy, r, color, fill }
     }
+
+    /// Like `new`, but rejects a non-finite center or a negative or non-finite radius.
+    pub fn try_new(cx: f64, cy: f64, r: f64, color: Color, fill: Color) -> Result<Circle> {
+        let circle = Circle::new(cx, cy, r, color, fill);
+        circle.validate()?;
+        Ok(circle)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        error::check_finite("circle", "center", self.cx)?;
+        error::check_finite("circle", "center", self.cy)?;
+        error::check_size("circle", "radius", self.r)
+    }
+
+    /// Whether `point` is on or inside the circle.
+    pub fn contains(&self, point: Point) -> bool {
+        (point.x - self.cx).hypot(point.y - self.cy) <= self.r
+    }
 }
 
 impl Into<XMLElement> for Circle {
@@ -199,22 +820,72 @@ impl Into<XMLElement> for Circle {
     }
 }
 
+impl Drawable for Circle {
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::new(self.cx - self.r, self.cy - self.r, self.cx + self.r, self.cy + self.r))
+    }
+
+    fn transform(&mut self, matrix: &Matrix3) {
+        let c = Point::new(self.cx, self.cy).transform(matrix);
+        self.cx = c.x;
+        self.cy = c.y;
+        self.r *= drawable::scale_factor(matrix);
+    }
+
+    fn render(&self) -> XMLElement {
+        self.clone().into()
+    }
+
+    fn box_clone(&self) -> Box<dyn Drawable> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn Drawable) -> bool {
+        other.as_any().downcast_ref::<Self>() == Some(self)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Line {
-    pub p1: Coordinate,
-    pub p2: Coordinate,
+    pub p1: Point,
+    pub p2: Point,
     pub color: Color,
     pub stroke_width: Option<f64>,
 }
 
 impl Line {
-    pub fn new<T: Into<Coordinate>>(p1: T, p2: T, color: Color) -> Line {
+    pub fn new<T: Into<Point>>(p1: T, p2: T, color: Color) -> Line {
         Line { p1: p1.into(), p2: p2.into(), color, stroke_width: None }
     }
 
+    /// Like `new`, but rejects non-finite end points.
+    pub fn try_new<T: Into<Point>>(p1: T, p2: T, color: Color) -> Result<Line> {
+        let line = Line::new(p1, p2, color);
+        line.validate()?;
+        Ok(line)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        error::check_coordinate("line", "start point", &self.p1)?;
+        error::check_coordinate("line", "end point", &self.p2)
+    }
+
     pub fn stroke_width(self, stroke_width: f64) -> Line {
         Line { stroke_width: Some(stroke_width), ..self }
     }
+
+    /// Distance from `point` to the line.
+    pub fn distance(&self, point: Point) -> f64 {
+        polygon::segment_distance(point, self.p1, self.p2)
+    }
 }
 
 impl Into<XMLElement> for Line {
@@ -228,9 +899,40 @@ impl Into<XMLElement> for Line {
     }
 }
 
+impl Drawable for Line {
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_points(vec![self.p1.tup(), self.p2.tup()])
+    }
+
+    fn transform(&mut self, matrix: &Matrix3) {
+        self.p1 = self.p1.transform(matrix);
+        self.p2 = self.p2.transform(matrix);
+    }
+
+    fn render(&self) -> XMLElement {
+        self.clone().into()
+    }
+
+    fn box_clone(&self) -> Box<dyn Drawable> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn Drawable) -> bool {
+        other.as_any().downcast_ref::<Self>() == Some(self)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Axis {
-    location: Coordinate,
+    location: Point,
     scale: f64,
 }
 
@@ -238,10 +940,9 @@ impl Axis {
     pub fn new(scale: f64) -> Axis {
         Axis { location: (0.0, 0.0).into(), scale }
     }
-}
 
-impl Into<XMLElement> for Axis {
-    fn into(self) -> XMLElement {
+    /// The lines making up the axis arrows.
+    pub fn lines(&self) -> Vec<Line> {
         let arrow_scale = self.scale / 10.0;
         let x_dir = self.location.translate(self.scale, 0.0);
         let y_dir = self.location.translate(0.0, self.scale);
@@ -250,57 +951,217 @@ impl Into<XMLElement> for Axis {
         let y_a1 = y_dir.translate(-arrow_scale, -arrow_scale);
         let y_a2 = y_dir.translate(arrow_scale, -arrow_scale);
         let color = Color::Grey;
-        XMLElement::new("g")
-            .element(Line::new(self.location, x_dir, color))
-            .element(Line::new(self.location, y_dir, color))
-            .element(Line::new(x_dir, x_a1, color))
-            .element(Line::new(x_dir, x_a2, color))
-            .element(Line::new(y_dir, y_a1, color))
-            .element(Line::new(y_dir, y_a2, color))
-            .element(Line::new(x_a1, x_a2, color))
+        vec![
+            Line::new(self.location, x_dir, color),
+            Line::new(self.location, y_dir, color),
+            Line::new(x_dir, x_a1, color),
+            Line::new(x_dir, x_a2, color),
+            Line::new(y_dir, y_a1, color),
+            Line::new(y_dir, y_a2, color),
+            Line::new(x_a1, x_a2, color),
+        ]
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let arrow_scale = self.scale / 10.0;
+        BoundingBox::point(self.location.x, self.location.y)
+            .union(BoundingBox::point(self.location.x + self.scale, self.location.y + self.scale))
+            .grow(arrow_scale)
+    }
+}
+
+impl Into<XMLElement> for Axis {
+    fn into(self) -> XMLElement {
+        self.lines().into_iter().fold(XMLElement::new("g"), |g, l| g.element(l))
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct World {
-    pub location: Coordinate,
-    pub elements: Vec<XMLElement>,
+    pub location: Frame,
+    pub elements: Vec<Element>,
     pub axis_scale: f64,
+    /// Whether the axis of the world is drawn.
+    pub show_axis: bool,
     pub name: String,
     pub stroke_width: Option<f64>,
 }
 
 impl World {
-    pub fn new<T: ToString>(name: T, location: Coordinate) -> World {
-        World { location, elements: vec![], axis_scale: 10.0, name: name.to_string(), stroke_width: None }
+    pub fn new<T: ToString, F: Into<Frame>>(name: T, location: F) -> World {
+        World {
+            location: location.into(),
+            elements: vec![],
+            axis_scale: 10.0,
+            show_axis: true,
+            name: name.to_string(),
+            stroke_width: None,
+        }
     }
-    pub fn add<X: Into<XMLElement>>(&mut self, x: X) {
+    pub fn add<X: Into<Element>>(&mut self, x: X) {
         self.elements.push(x.into())
     }
 
-    pub fn add_all<XN>(&mut self, xn: XN) where XN: IntoIterator, XN::Item: Into<XMLElement> {
+    pub fn add_all<XN>(&mut self, xn: XN) where XN: IntoIterator, XN::Item: Into<Element> {
         xn.into_iter().for_each(|x| self.elements.push(x.into()))
     }
     pub fn axis_scale(self, axis_scale: f64) -> Self {
         World { axis_scale, ..self }
     }
+    pub fn show_axis(self, show_axis: bool) -> Self {
+        World { show_axis, ..self }
+    }
     pub fn stroke_width_opt(self, stroke_width: Option<f64>) -> Self {
         World { stroke_width, ..self }
     }
+
+    /// The axis of the world when it is drawn.
+    pub fn axis(&self) -> Option<Axis> {
+        if self.show_axis { Some(Axis::new(self.axis_scale)) } else { None }
+    }
+
+    /// Check the placement of the world and all its elements.
+    pub fn validate(&self) -> Result<()> {
+        let element = format!("world '{}'", self.name);
+        error::check_coordinate(&element, "location", &self.location.origin)?;
+        error::check_finite(&element, "rotation", self.location.rotation)?;
+        error::check_finite(&element, "scale", self.location.scale_x)?;
+        error::check_finite(&element, "scale", self.location.scale_y)?;
+        error::check_size(&element, "axis scale", self.axis_scale)?;
+        self.elements.iter().try_for_each(|e| e.validate())
+    }
+
+    /// The matrix mapping world coordinates to coordinates of the parent.
+    pub fn matrix(&self) -> Matrix3 {
+        self.location.matrix()
+    }
 }
 
-impl Into<XMLElement> for World {
-    fn into(self) -> XMLElement {
-        let matrix = self.location.matrix();
+impl World {
+    /// Render to SVG with the path data of all elements written in
+    /// `path_format`, rounded to `precision` decimal places.
+    pub fn into_svg(self, path_format: PathFormat, precision: Option<usize>) -> XMLElement {
         XMLElement::new("g")
             .attr("id", format!("world-{}", self.name))
-            .attr("transform", format!("translate({} {}) matrix({} {} {} {} {} {})",
-                                       self.location.x, self.location.y,
-                                       matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m31, matrix.m32
-            ))
+            .attr("transform", self.matrix().to_svg())
             .attr_opt("stroke-width", self.stroke_width)
-            .element(Axis::new(self.axis_scale))
+            .element_opt(self.axis())
             .element(Title(self.name))
-            .elements(self.elements)
+            .elements(self.elements.into_iter().map(|e| e.into_svg(path_format, precision)).collect())
+    }
+}
+
+impl Into<XMLElement> for World {
+    fn into(self) -> XMLElement {
+        self.into_svg(PathFormat::new(), None)
+    }
+}
+
+impl Drawable for World {
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let axis = self.axis().map(|a| a.bounding_box());
+        let local = self.elements.iter()
+            .filter_map(|e| e.bounding_box())
+            .fold(axis, |acc, b| Some(BoundingBox::union_opt(acc, b)))?;
+        Some(local.transform(&self.matrix()))
+    }
+
+    /// Moves the origin of the world and composes the rotation, reflection
+    /// and scale of `matrix` with its own; shear is not representable.
+    fn transform(&mut self, matrix: &Matrix3) {
+        self.location = self.location.transform(matrix);
+    }
+
+    fn render(&self) -> XMLElement {
+        self.clone().into()
+    }
+
+    fn box_clone(&self) -> Box<dyn Drawable> {
+        Box::new(self.clone())
     }
-}
\ No newline at end of file
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn Drawable) -> bool {
+        other.as_any().downcast_ref::<Self>() == Some(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_approx_eq::assert_approx_eq;
+
+    use simple_xml_serialize::XMLElement;
+
+    use crate::{Area, Drawable, Error};
+    use crate::graphic::PathBuilder;
+
+    #[test]
+    fn builder() {
+        // a D shaped bed: a straight edge and a half circle back to the start
+        let area = PathBuilder::new(0.0, 0.0).dy(4.0).arc(2.0, 2.0, 0.0, false, false, 0.0, -4.0).area("bed");
+        assert_eq!(2, area.corners.len());
+        assert!(area.validate().is_ok());
+        assert!(Area::try_new("strip", vec![(0.0, 0.0), (0.0, 4.0)]).is_err());
+        let xml: XMLElement = area.clone().into();
+        assert!(xml.to_string().contains("d=\"M0,0 L0,4 A2,2,0,0,0,0,0 z\""));
+        let bbox = area.bounding_box().unwrap();
+        assert_approx_eq!(bbox.max_x, 2.0, 0.01);
+        assert_approx_eq!(bbox.min_x, 0.0);
+    }
+
+    #[test]
+    fn subpaths() {
+        let builder = PathBuilder::new(0.0, 0.0)
+            .to(4.0, 0.0).turn(90.0).forward(3.0).close()
+            .move_to(10.0, 0.0).heading(90.0, 2.0);
+        let paths = builder.paths("edge");
+        assert_eq!(2, paths.len());
+        assert_eq!("edge-1", paths[0].name);
+        assert_eq!(4, paths[0].points.len());
+        assert_approx_eq!(paths[0].points[2].x, 4.0);
+        assert_approx_eq!(paths[0].points[2].y, 3.0);
+        assert_approx_eq!(paths[1].points[1].y, 2.0);
+        let triangle = PathBuilder::new(0.0, 0.0).to(4.0, 0.0).to(4.0, 3.0).close().area("triangle");
+        assert_eq!(3, triangle.corners.len());
+    }
+
+    #[test]
+    fn single_path() {
+        let path = PathBuilder::new(0.0, 0.0).to(4.0, 0.0).to(4.0, 3.0).path("edge").unwrap();
+        assert_eq!(3, path.points.len());
+        let two = PathBuilder::new(0.0, 0.0).to(4.0, 0.0).move_to(10.0, 0.0).dx(2.0).path("edge");
+        match two {
+            Err(Error::TooManySubpaths { element, found }) => {
+                assert_eq!(("path 'edge'".to_string(), 2), (element, found));
+            }
+            r => panic!("unexpected {:?}", r),
+        }
+        assert_eq!(vec![(0.0, 0.0), (4.0, 0.0)], PathBuilder::new(0.0, 0.0).dx(4.0).done().unwrap());
+        assert!(matches!(PathBuilder::new(0.0, 0.0).dx(4.0).move_to(10.0, 0.0).dx(2.0).done(),
+                         Err(Error::TooManySubpaths { found: 2, .. })));
+        // the extra subpath of an area is a hole
+        let lawn = PathBuilder::new(0.0, 0.0).dx(10.0).dy(10.0).dx(-10.0).close()
+            .move_to(4.0, 4.0).dy(2.0).dx(2.0).dy(-2.0).close()
+            .area("lawn");
+        assert_eq!(1, lawn.holes.len());
+        assert_eq!(96.0, lawn.surface());
+    }
+
+    #[test]
+    fn close_snaps_to_start() {
+        // the last of these polar steps ends a rounding error away from the start
+        let square = PathBuilder::new(0.0, 0.0)
+            .heading(0.0, 3.0).turn(90.0).forward(3.0).turn(90.0).forward(3.0).turn(90.0).forward(3.0)
+            .close();
+        let area = square.area("square");
+        assert_eq!(4, area.corners.len());
+        assert_approx_eq!(area.surface(), 9.0);
+    }
+}
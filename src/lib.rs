@@ -1,19 +1,46 @@
 // (c) 2019 Joost Yervante Damad <joost@damad.be>
 
+pub use bounding_box::BoundingBox;
 pub use color::Color;
+#[allow(deprecated)]
 pub use coordinate::Coordinate;
+pub use drawable::Drawable;
+pub use element::Element;
+pub use error::{Error, Result};
+pub use frame::Frame;
 pub use graphic::{Area, Circle};
+pub use point::Point;
 pub use svg::Document;
 pub use text::Label;
 
+pub mod backend;
+pub mod boolean;
+pub mod bounding_box;
+pub mod drawable;
+pub mod dxf;
+pub mod geojson;
 pub mod graphic;
 pub mod color;
 pub mod coordinate;
+pub mod curve;
+pub mod element;
+pub mod error;
+pub mod frame;
 pub mod text;
 pub mod matrix2;
 pub mod matrix3;
+pub mod offset;
+pub mod plan;
+pub mod path_data;
+pub mod pdf;
+pub mod point;
+pub mod polygon;
+pub mod raster;
 pub mod tree;
 pub mod vector2;
 pub mod vector3;
+pub mod watch;
 pub mod svg;
 pub mod util;
+
+mod font;
@@ -0,0 +1,253 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! Render plan description files.
+
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+use jyd2d::{Document, Element};
+use jyd2d::pdf::{Paper, PdfOptions};
+use jyd2d::plan::Plan;
+use jyd2d::watch::Watcher;
+
+const USAGE: &str = "\
+usage: jyd2d [options] <plan.toml|plan.json|plan.yaml>
+
+Renders a plan description; the output format follows the extension
+of the output file: svg, png, pdf, dxf or geojson.
+
+options:
+  -o, --output <file>        output file, can be given multiple times
+                             (default: the plan file with extension .svg)
+  -p, --pretty               pretty print SVG output
+      --pixels-per-unit <n>  resolution of SVG and PNG output
+      --margin <m>           fit the document to its content with margin m
+  -l, --layer <name>         only render the top level world <name>, can be
+                             given multiple times; 'Document' selects the
+                             elements outside of any world
+      --scale <n>            PDF print scale 1:n (default 100)
+      --paper <a4|a3>        PDF paper size (default a4)
+      --landscape            PDF landscape orientation
+  -w, --watch                re-render whenever the plan file changes
+      --interval <ms>        polling interval in watch mode (default 500)
+  -h, --help                 show this help";
+
+struct Options {
+    plan: String,
+    outputs: Vec<String>,
+    pretty: bool,
+    pixels_per_unit: Option<i64>,
+    margin: Option<f64>,
+    layers: Vec<String>,
+    pdf: PdfOptions,
+    watch: bool,
+    interval: u64,
+}
+
+fn value<T: std::str::FromStr>(args: &mut impl Iterator<Item=String>, option: &str) -> Result<T, String> {
+    let v = args.next().ok_or_else(|| format!("missing value for {}", option))?;
+    v.parse().map_err(|_| format!("invalid value '{}' for {}", v, option))
+}
+
+fn parse_args<I: Iterator<Item=String>>(mut args: I) -> Result<Options, String> {
+    let mut plan = None;
+    let mut options = Options {
+        plan: String::new(),
+        outputs: vec![],
+        pretty: false,
+        pixels_per_unit: None,
+        margin: None,
+        layers: vec![],
+        pdf: PdfOptions::new(Paper::A4, 100.0),
+        watch: false,
+        interval: 500,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "-o" | "--output" => options.outputs.push(value(&mut args, &arg)?),
+            "-p" | "--pretty" => options.pretty = true,
+            "--pixels-per-unit" => options.pixels_per_unit = Some(value(&mut args, &arg)?),
+            "--margin" => options.margin = Some(value(&mut args, &arg)?),
+            "-l" | "--layer" => options.layers.push(value(&mut args, &arg)?),
+            "--scale" => options.pdf.scale = value(&mut args, &arg)?,
+            "--paper" => {
+                options.pdf.paper = match value::<String>(&mut args, &arg)?.to_lowercase().as_str() {
+                    "a4" => Paper::A4,
+                    "a3" => Paper::A3,
+                    p => return Err(format!("unknown paper size '{}'", p)),
+                }
+            }
+            "--landscape" => options.pdf.landscape = true,
+            "-w" | "--watch" => options.watch = true,
+            "--interval" => options.interval = value(&mut args, &arg)?,
+            a if a.starts_with('-') => return Err(format!("unknown option '{}'", a)),
+            _ if plan.is_some() => return Err(format!("unexpected argument '{}'", arg)),
+            _ => plan = Some(arg),
+        }
+    }
+    options.plan = plan.ok_or("missing plan file")?;
+    options.pdf.validate().map_err(|e| e.to_string())?;
+    if options.outputs.is_empty() {
+        options.outputs.push(Path::new(&options.plan).with_extension("svg").to_string_lossy().into_owned());
+    }
+    Ok(options)
+}
+
+fn select_layers(document: &mut Document, layers: &[String]) {
+    if layers.is_empty() {
+        return;
+    }
+    let document_layer = layers.iter().any(|l| l == "Document");
+    document.retain(|e| match e {
+        Element::World(w) => layers.contains(&w.name),
+        _ => document_layer,
+    });
+}
+
+fn save(document: &Document, output: &str, options: &Options) -> io::Result<()> {
+    let extension = Path::new(output).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "svg" if options.pretty => document.pretty_save(output),
+        "svg" => document.save(output),
+        "png" => document.save_png(output),
+        "pdf" => document.save_pdf(output, &options.pdf).map_err(io_error),
+        "dxf" => document.save_dxf(output),
+        "geojson" => document.save_geojson(output),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("unknown output format '{}'", extension))),
+    }
+}
+
+fn io_error(error: jyd2d::Error) -> io::Error {
+    match error {
+        jyd2d::Error::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidInput, e.to_string()),
+    }
+}
+
+fn run(options: &Options) -> jyd2d::Result<()> {
+    let mut plan = Plan::load(&options.plan)?;
+    if let Some(pixels_per_unit) = options.pixels_per_unit {
+        plan.document.pixels_per_unit = pixels_per_unit;
+    }
+    if let Some(margin) = options.margin {
+        plan.document.width = None;
+        plan.document.height = None;
+        plan.document.margin = margin;
+    }
+    let mut document = plan.document()?;
+    select_layers(&mut document, &options.layers);
+    for output in &options.outputs {
+        save(&document, output, options).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", output, e)))?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("jyd2d: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if options.watch {
+        let mut watcher = Watcher::new(vec![options.plan.clone()]);
+        watcher.run(Duration::from_millis(options.interval), || run(&options), |result| {
+            match result {
+                Ok(()) => eprintln!("jyd2d: rendered {}", options.outputs.join(", ")),
+                Err(e) => eprintln!("jyd2d: {}", e),
+            }
+            true
+        });
+    } else if let Err(e) = run(&options) {
+        eprintln!("jyd2d: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use jyd2d::{Area, Document};
+    use jyd2d::graphic::World;
+    use jyd2d::pdf::Paper;
+
+    use crate::{Options, parse_args, select_layers};
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn defaults() {
+        let options = parse(&["garden.toml"]).unwrap();
+        assert_eq!("garden.toml", options.plan);
+        assert_eq!(vec!["garden.svg"], options.outputs);
+        assert!(!options.pretty && !options.watch && !options.pdf.landscape);
+        assert_eq!((None, None), (options.pixels_per_unit, options.margin));
+        assert_eq!(Paper::A4, options.pdf.paper);
+        assert_eq!(100.0, options.pdf.scale);
+        assert_eq!(500, options.interval);
+    }
+
+    #[test]
+    fn flags() {
+        let options = parse(&["-o", "a.svg", "--output", "a.pdf", "-p", "--pixels-per-unit", "20", "--margin",
+                              "2.5", "-l", "site", "--layer", "Document", "--scale", "50", "--paper", "A3",
+                              "--landscape", "-w", "--interval", "100", "plan.yaml"]).unwrap();
+        assert_eq!("plan.yaml", options.plan);
+        assert_eq!(vec!["a.svg", "a.pdf"], options.outputs);
+        assert!(options.pretty && options.watch && options.pdf.landscape);
+        assert_eq!((Some(20), Some(2.5)), (options.pixels_per_unit, options.margin));
+        assert_eq!(vec!["site", "Document"], options.layers);
+        assert_eq!((Paper::A3, 50.0), (options.pdf.paper, options.pdf.scale));
+        assert_eq!(100, options.interval);
+        // options may follow the plan file
+        assert!(parse(&["plan.yaml", "--pretty"]).unwrap().pretty);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Some("missing plan file".to_string()), parse(&[]).err());
+        assert_eq!(Some("missing plan file".to_string()), parse(&["-p"]).err());
+        assert_eq!(Some("unknown option '--bogus'".to_string()), parse(&["--bogus", "a.toml"]).err());
+        assert_eq!(Some("unexpected argument 'b.toml'".to_string()), parse(&["a.toml", "b.toml"]).err());
+        assert_eq!(Some("missing value for -o".to_string()), parse(&["a.toml", "-o"]).err());
+        assert_eq!(Some("invalid value 'x' for --scale".to_string()), parse(&["a.toml", "--scale", "x"]).err());
+        assert_eq!(Some("unknown paper size 'letter'".to_string()), parse(&["a.toml", "--paper", "letter"]).err());
+        assert_eq!(Some("PDF output has an out of range scale".to_string()), parse(&["a.toml", "--scale", "0"]).err());
+    }
+
+    fn document() -> Document {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        doc.add(Area::new("lawn", vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]));
+        doc.add(World::new("site", (0.0, 0.0)));
+        doc.add(World::new("shed", (5.0, 5.0)));
+        doc
+    }
+
+    fn names(doc: &Document) -> Vec<String> {
+        doc.children().iter().filter_map(|e| e.name().map(|n| n.to_string())).collect()
+    }
+
+    #[test]
+    fn layers() {
+        let mut doc = document();
+        select_layers(&mut doc, &[]);
+        assert_eq!(vec!["lawn", "site", "shed"], names(&doc));
+        select_layers(&mut doc, &["shed".to_string()]);
+        assert_eq!(vec!["shed"], names(&doc));
+        let mut doc = document();
+        select_layers(&mut doc, &["Document".to_string(), "site".to_string()]);
+        assert_eq!(vec!["lawn", "site"], names(&doc));
+        let mut doc = document();
+        select_layers(&mut doc, &["pond".to_string()]);
+        assert!(doc.children().is_empty());
+    }
+}
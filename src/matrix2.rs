@@ -212,7 +212,7 @@ impl ops::Mul<Matrix2> for Matrix2 {
     fn mul(self, rhs: Matrix2) -> Matrix2 {
         Matrix2 {
             m11: self.m11 * rhs.m11 + self.m12 * rhs.m21,
-            m12: self.m11 * rhs.m21 + self.m12 * rhs.m22,
+            m12: self.m11 * rhs.m12 + self.m12 * rhs.m22,
             m21: self.m21 * rhs.m11 + self.m22 * rhs.m21,
             m22: self.m21 * rhs.m12 + self.m22 * rhs.m22,
         }
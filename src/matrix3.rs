@@ -1,4 +1,6 @@
 use std::ops;
+use crate::error::{Error, Result};
+use crate::matrix2::Matrix2;
 use crate::vector2::Vector2;
 use crate::vector3::Vector3;
 use std::f64::consts::PI;
@@ -110,9 +112,231 @@ impl Matrix3 {
         }
     }
 
+    /// Shear along x by `s` times y, like SVG `skewX` with tan(angle) = `s`.
+    pub fn shear_x(s: f64) -> Matrix3 {
+        Matrix3::linear(Matrix2::shear_x(s))
+    }
+
+    /// Shear along y by `s` times x, like SVG `skewY` with tan(angle) = `s`.
+    pub fn shear_y(s: f64) -> Matrix3 {
+        Matrix3::linear(Matrix2::shear_y(s))
+    }
+
+    /// Reflection in the axis through the origin perpendicular to unit vector `n`.
+    pub fn reflect_n(n: Vector2) -> Matrix3 {
+        Matrix3::linear(Matrix2::reflect_n(n))
+    }
+
+    /// The affine transform applying `m` and then translating by `translation`.
+    pub fn from_matrix2(m: Matrix2, translation: Vector2) -> Matrix3 {
+        Matrix3 {
+            m11: m.m11,
+            m12: m.m12,
+            m21: m.m21,
+            m22: m.m22,
+            m31: translation.x,
+            m32: translation.y,
+            ..Matrix3::unit()
+        }
+    }
+
+    fn linear(m: Matrix2) -> Matrix3 {
+        Matrix3::from_matrix2(m, Vector2::new(0.0, 0.0))
+    }
+
+    /// The linear part of an affine transform.
+    pub fn matrix2(self) -> Matrix2 {
+        Matrix2::new(self.m11, self.m12, self.m21, self.m22)
+    }
+
+    /// The translation part of an affine transform.
+    pub fn translation(self) -> Vector2 {
+        Vector2::new(self.m31, self.m32)
+    }
+
     pub fn determinant(self) -> f64 {
         self.m11 * self.m22 * self.m33 + self.m12 * self.m23 * self.m31 + self.m13 * self.m21 * self.m32
-            - self.m12 * self.m22 * self.m31 - self.m12 * self.m21 * self.m33 - self.m11 * self.m23 * self.m32
+            - self.m13 * self.m22 * self.m31 - self.m12 * self.m21 * self.m33 - self.m11 * self.m23 * self.m32
+    }
+
+    pub fn adjoint(self) -> Matrix3 {
+        Matrix3 {
+            m11: self.m22 * self.m33 - self.m23 * self.m32,
+            m12: self.m13 * self.m32 - self.m12 * self.m33,
+            m13: self.m12 * self.m23 - self.m13 * self.m22,
+            m21: self.m23 * self.m31 - self.m21 * self.m33,
+            m22: self.m11 * self.m33 - self.m13 * self.m31,
+            m23: self.m13 * self.m21 - self.m11 * self.m23,
+            m31: self.m21 * self.m32 - self.m22 * self.m31,
+            m32: self.m12 * self.m31 - self.m11 * self.m32,
+            m33: self.m11 * self.m22 - self.m12 * self.m21,
+        }
+    }
+
+    /// The transform undoing this one; not finite when the determinant is 0.
+    pub fn inverse(self) -> Matrix3 {
+        self.adjoint() / self.determinant()
+    }
+
+    /// Split an affine transform into the steps building it, see `Decomposition`.
+    pub fn decompose(self) -> Decomposition {
+        let scale_x = self.m11.hypot(self.m12);
+        let rotate = self.m12.atan2(self.m11);
+        let (sin, cos) = rotate.sin_cos();
+        let scale_y = self.m22 * cos - self.m21 * sin;
+        let shear = if scale_y != 0.0 { (self.m21 * cos + self.m22 * sin) / scale_y } else { 0.0 };
+        Decomposition {
+            flip_y: scale_y < 0.0,
+            scale_x,
+            scale_y: scale_y.abs(),
+            skew_x: shear.atan().to_degrees(),
+            rotate: rotate.to_degrees(),
+            translate: self.translation(),
+        }
+    }
+
+    /// The transform as an SVG `matrix(...)` transform.
+    pub fn to_svg(&self) -> String {
+        format!("matrix({} {} {} {} {} {})", self.m11, self.m12, self.m21, self.m22, self.m31, self.m32)
+    }
+
+    /// Parse an SVG `transform` attribute: a list of `matrix`, `translate`,
+    /// `scale`, `rotate`, `skewX` and `skewY` transforms, the last applied first.
+    pub fn parse_svg(transform: &str) -> Result<Matrix3> {
+        let invalid = || Error::Parse(format!("invalid transform '{}'", transform));
+        let mut result = Matrix3::unit();
+        let mut rest = transform.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        while !rest.is_empty() {
+            let open = rest.find('(').ok_or_else(invalid)?;
+            let close = rest.find(')').ok_or_else(invalid)?;
+            if close < open {
+                return Err(invalid());
+            }
+            let args = rest[open + 1..close].split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|a| !a.is_empty())
+                .map(|a| a.parse::<f64>().map_err(|_| invalid()))
+                .collect::<Result<Vec<f64>>>()?;
+            let m = match (rest[..open].trim(), args.as_slice()) {
+                ("matrix", &[a, b, c, d, e, f]) => Matrix3::from_matrix2(Matrix2::new(a, b, c, d), Vector2::new(e, f)),
+                ("translate", &[x]) => Matrix3::translate(Vector2::new(x, 0.0)),
+                ("translate", &[x, y]) => Matrix3::translate(Vector2::new(x, y)),
+                ("scale", &[s]) => Matrix3::scale(s, s),
+                ("scale", &[sx, sy]) => Matrix3::scale(sx, sy),
+                ("rotate", &[a]) => Matrix3::rotate(a),
+                ("rotate", &[a, x, y]) => Matrix3::builder()
+                    .translate(Vector2::new(-x, -y))
+                    .rotate(a)
+                    .translate(Vector2::new(x, y))
+                    .build(),
+                ("skewX", &[a]) => Matrix3::shear_x(a.to_radians().tan()),
+                ("skewY", &[a]) => Matrix3::shear_y(a.to_radians().tan()),
+                _ => return Err(invalid()),
+            };
+            result = m * result;
+            rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        }
+        Ok(result)
+    }
+
+    pub fn assert_approx_eq(self, rhs: Matrix3) {
+        use assert_approx_eq::assert_approx_eq;
+        assert_approx_eq!(self.m11, rhs.m11);
+        assert_approx_eq!(self.m12, rhs.m12);
+        assert_approx_eq!(self.m13, rhs.m13);
+        assert_approx_eq!(self.m21, rhs.m21);
+        assert_approx_eq!(self.m22, rhs.m22);
+        assert_approx_eq!(self.m23, rhs.m23);
+        assert_approx_eq!(self.m31, rhs.m31);
+        assert_approx_eq!(self.m32, rhs.m32);
+        assert_approx_eq!(self.m33, rhs.m33);
+    }
+}
+
+/// An affine transform as separate steps, applied in this order: a flip of
+/// the y axis, scaling, skewing along x, rotating and translating.
+///
+/// A flip of the x axis is a flip of the y axis followed by a half turn.
+/// Angles are in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decomposition {
+    pub flip_y: bool,
+    pub scale_x: f64,
+    pub scale_y: f64,
+    pub skew_x: f64,
+    pub rotate: f64,
+    pub translate: Vector2,
+}
+
+impl Decomposition {
+    pub fn matrix(&self) -> Matrix3 {
+        Matrix3::builder()
+            .flip_y(self.flip_y)
+            .scale(self.scale_x, self.scale_y)
+            .shear_x(self.skew_x.to_radians().tan())
+            .rotate(self.rotate)
+            .translate(self.translate)
+            .build()
+    }
+
+    /// The steps as an SVG transform list, leaving out steps that do nothing.
+    pub fn to_svg(&self) -> String {
+        let mut steps = vec![];
+        if self.translate.x != 0.0 || self.translate.y != 0.0 {
+            steps.push(format!("translate({} {})", self.translate.x, self.translate.y));
+        }
+        if self.rotate != 0.0 {
+            steps.push(format!("rotate({})", self.rotate));
+        }
+        if self.skew_x != 0.0 {
+            steps.push(format!("skewX({})", self.skew_x));
+        }
+        let sy = if self.flip_y { -self.scale_y } else { self.scale_y };
+        if self.scale_x != 1.0 || sy != 1.0 {
+            steps.push(format!("scale({} {})", self.scale_x, sy));
+        }
+        steps.join(" ")
+    }
+}
+
+impl ops::Add<Matrix3> for Matrix3 {
+    type Output = Matrix3;
+
+    fn add(self, rhs: Matrix3) -> Matrix3 {
+        Matrix3 {
+            m11: self.m11 + rhs.m11,
+            m12: self.m12 + rhs.m12,
+            m13: self.m13 + rhs.m13,
+            m21: self.m21 + rhs.m21,
+            m22: self.m22 + rhs.m22,
+            m23: self.m23 + rhs.m23,
+            m31: self.m31 + rhs.m31,
+            m32: self.m32 + rhs.m32,
+            m33: self.m33 + rhs.m33,
+        }
+    }
+}
+
+impl ops::Sub<Matrix3> for Matrix3 {
+    type Output = Matrix3;
+
+    fn sub(self, rhs: Matrix3) -> Matrix3 {
+        self + (-rhs)
+    }
+}
+
+impl ops::Neg for Matrix3 {
+    type Output = Matrix3;
+
+    fn neg(self) -> Matrix3 {
+        self * -1.0
+    }
+}
+
+impl ops::Div<f64> for Matrix3 {
+    type Output = Matrix3;
+
+    fn div(self, rhs: f64) -> Matrix3 {
+        self * (1.0 / rhs)
     }
 }
 
@@ -147,7 +371,7 @@ impl ops::Mul<Matrix3> for f64 {
             m23: rhs.m23 * self,
             m31: rhs.m31 * self,
             m32: rhs.m32 * self,
-            m33: rhs.m23 * self,
+            m33: rhs.m33 * self,
         }
     }
 }
@@ -219,7 +443,85 @@ impl Matrix3Build {
         self
     }
 
+    pub fn shear_x(mut self, s: f64) -> Matrix3Build {
+        self.result *= Matrix3::shear_x(s);
+        self
+    }
+
+    pub fn shear_y(mut self, s: f64) -> Matrix3Build {
+        self.result *= Matrix3::shear_y(s);
+        self
+    }
+
+    pub fn reflect_n(mut self, n: Vector2) -> Matrix3Build {
+        self.result *= Matrix3::reflect_n(n);
+        self
+    }
+
     pub fn build(self) -> Matrix3 {
         self.result
     }
 }
+
+
+#[cfg(test)]
+mod test {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::matrix2::Matrix2;
+    use crate::matrix3::*;
+
+    fn affine() -> Matrix3 {
+        Matrix3::builder().flip_y(true).scale(2.0, 3.0).shear_x(0.5).rotate(30.0)
+            .translate(Vector2::new(5.0, -7.0)).build()
+    }
+
+    #[test]
+    fn inverse() {
+        let m = affine();
+        (m * m.inverse()).assert_approx_eq(Matrix3::unit());
+        m.inverse().inverse().assert_approx_eq(m);
+        assert_approx_eq!(m.inverse().determinant(), 1.0 / m.determinant());
+        assert_approx_eq!(m.determinant(), -6.0);
+        let p = Vector3::new(1.0, 2.0, 1.0) * m * m.inverse();
+        assert_approx_eq!(p.x, 1.0);
+        assert_approx_eq!(p.y, 2.0);
+        (m - m).assert_approx_eq(Matrix3::zero());
+        (2.0 * m).assert_approx_eq(m + m);
+    }
+
+    #[test]
+    fn decompose() {
+        let d = affine().decompose();
+        assert!(d.flip_y);
+        assert_approx_eq!(d.scale_x, 2.0);
+        assert_approx_eq!(d.scale_y, 3.0);
+        assert_approx_eq!(d.skew_x.to_radians().tan(), 0.5);
+        assert_approx_eq!(d.rotate, 30.0);
+        d.matrix().assert_approx_eq(affine());
+        let m = Matrix3::from_matrix2(Matrix2::rotate(1.0) * Matrix2::scale(2.0, 2.0), Vector2::new(1.0, 2.0));
+        m.decompose().matrix().assert_approx_eq(m);
+        assert_eq!(Vector2::new(1.0, 2.0), m.translation());
+        m.matrix2().assert_approx_eq(Matrix2::rotate(1.0) * Matrix2::scale(2.0, 2.0));
+        let reflect = Matrix3::reflect_n(Vector2::new(1.0, 1.0).normalize());
+        reflect.decompose().matrix().assert_approx_eq(reflect);
+        assert_eq!("", Matrix3::unit().decompose().to_svg());
+    }
+
+    #[test]
+    fn svg() {
+        let m = affine();
+        Matrix3::parse_svg(&m.to_svg()).unwrap().assert_approx_eq(m);
+        Matrix3::parse_svg(&m.decompose().to_svg()).unwrap().assert_approx_eq(m);
+        let rotated = Matrix3::parse_svg("translate(10,0) rotate(90 1 1)").unwrap();
+        let p = Vector3::new(2.0, 1.0, 1.0) * rotated;
+        assert_approx_eq!(p.x, 11.0);
+        assert_approx_eq!(p.y, 2.0);
+        Matrix3::parse_svg(" scale(2) skewY(45)").unwrap()
+            .assert_approx_eq(Matrix3::shear_y(1.0) * Matrix3::scale(2.0, 2.0));
+        assert_eq!(Matrix3::unit(), Matrix3::parse_svg("").unwrap());
+        assert!(Matrix3::parse_svg("rotate(1, 2)").is_err());
+        assert!(Matrix3::parse_svg("spin(1)").is_err());
+        assert!(Matrix3::parse_svg("scale(1").is_err());
+    }
+}
@@ -0,0 +1,247 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! Offsetting polygons and buffering polylines and points.
+//!
+//! The band within a distance of the edges is built from a rectangle along
+//! every edge and a join at every corner, merged with `boolean::union_all`.
+//! An offset polygon is the polygon with that band added or cut out.
+
+use std::f64::consts::PI;
+
+use crate::Point;
+use crate::boolean::{self, Operation};
+use crate::curve;
+use crate::vector2::Vector2;
+
+/// Corners are mitered up to this ratio of miter length to distance, like
+/// the SVG `stroke-miterlimit` default; sharper corners are beveled.
+pub const MITER_LIMIT: f64 = 4.0;
+
+/// How corners, and the ends of polylines, are shaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Join {
+    /// Sharp corners, up to `MITER_LIMIT`; polylines end flat at their ends.
+    Miter,
+    /// Rounded corners and ends.
+    Round,
+    /// Corners and ends cut off square at the distance beyond them.
+    Square,
+}
+
+fn coordinate(v: Vector2) -> Point {
+    Point::new(v.x, v.y)
+}
+
+/// Unit vector to the left of `v`.
+fn left(v: Vector2) -> Vector2 {
+    Vector2::new(-v.y, v.x).normalize()
+}
+
+/// A polygon approximating the circle around `center` within `curve::TOLERANCE`.
+fn circle(center: Vector2, r: f64) -> Vec<Point> {
+    let step = 2.0 * (1.0 - (curve::TOLERANCE / r).min(1.0)).acos();
+    let n = ((2.0 * PI / step).ceil() as usize).clamp(8, 256);
+    (0..n).map(|i| {
+        let a = 2.0 * PI * i as f64 / n as f64;
+        coordinate(center + Vector2::new(a.cos(), a.sin()) * r)
+    }).collect()
+}
+
+/// The join at `corner` between edges in directions `d1` and `d2`, on the
+/// outside of the turn.
+fn join(corner: Vector2, d1: Vector2, d2: Vector2, distance: f64, join: Join) -> Option<Vec<Point>> {
+    let cross = d1.perp_dot_product(d2);
+    let outside = if cross > 0.0 { -1.0 } else { 1.0 };
+    let (n1, n2) = (left(d1) * (outside * distance), left(d2) * (outside * distance));
+    match join {
+        Join::Round => Some(circle(corner, distance)),
+        _ if cross == 0.0 && d1.dot_product(d2) > 0.0 => None,
+        Join::Miter => {
+            let bisector = n1 + n2;
+            let cos = bisector.length() / (2.0 * distance);
+            if cos > 1.0 / MITER_LIMIT {
+                let miter = corner + bisector.normalize() * (distance / cos);
+                Some(vec![coordinate(corner), coordinate(corner + n1), coordinate(miter), coordinate(corner + n2)])
+            } else {
+                Some(vec![coordinate(corner), coordinate(corner + n1), coordinate(corner + n2)])
+            }
+        }
+        Join::Square => {
+            let (t1, t2) = (d1.normalize() * distance, d2.normalize() * distance);
+            Some(vec![coordinate(corner), coordinate(corner + n1), coordinate(corner + n1 + t1),
+                      coordinate(corner + n2 - t2), coordinate(corner + n2)])
+        }
+    }
+}
+
+/// The pieces of the band within `distance` of the polyline through `points`.
+fn band(points: &[Point], closed: bool, distance: f64, shape: Join) -> Vec<Vec<Vec<Point>>> {
+    let mut points: Vec<Vector2> = points.iter().map(Vector2::from).collect();
+    points.dedup_by(|a, b| (*a - *b).length() < 1e-9);
+    if closed && points.len() > 1 && (points[0] - points[points.len() - 1]).length() < 1e-9 {
+        points.pop();
+    }
+    let n = points.len();
+    if n == 1 || (closed && n == 2) {
+        return points.iter().map(|&p| vec![point(coordinate(p), distance, shape)]).collect();
+    }
+    let edges = if closed { n } else { n - 1 };
+    let mut pieces = vec![];
+    for i in 0..edges {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let d = left(b - a) * distance;
+        let (mut a, mut b) = (a, b);
+        if shape == Join::Square && !closed {
+            let t = (b - a).normalize() * distance;
+            if i == 0 {
+                a = a - t;
+            }
+            if i == edges - 1 {
+                b = b + t;
+            }
+        }
+        pieces.push(vec![vec![coordinate(a + d), coordinate(a - d), coordinate(b - d), coordinate(b + d)]]);
+    }
+    let corners = if closed { 0..n } else { 1..n - 1 };
+    for i in corners {
+        let (a, b, c) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+        pieces.extend(join(b, b - a, c - b, distance, shape).map(|j| vec![j]));
+    }
+    if shape == Join::Round && !closed {
+        pieces.push(vec![circle(points[0], distance)]);
+        pieces.push(vec![circle(points[n - 1], distance)]);
+    }
+    pieces
+}
+
+/// Grow polygon `rings`, the outline followed by its holes, by `distance`,
+/// or shrink it when `distance` is negative.
+pub fn polygon(rings: &[Vec<Point>], distance: f64, join: Join) -> Vec<Vec<Vec<Point>>> {
+    if distance == 0.0 {
+        return boolean::apply(Operation::Union, rings, &[]);
+    }
+    let pieces: Vec<_> = rings.iter().flat_map(|r| band(r, true, distance.abs(), join)).collect();
+    let band = boolean::union_all(&pieces);
+    let operation = if distance > 0.0 { Operation::Union } else { Operation::Difference };
+    boolean::apply_all(operation, &[rings.to_vec()], &band)
+}
+
+/// The polygons covering everything within `distance` of the polyline through `points`.
+pub fn polyline(points: &[Point], distance: f64, join: Join) -> Vec<Vec<Vec<Point>>> {
+    if distance <= 0.0 || points.is_empty() {
+        return vec![];
+    }
+    boolean::union_all(&band(points, false, distance, join))
+}
+
+/// The outline of everything within `distance` of `center`: a circle for
+/// `Join::Round`, a square otherwise.
+pub fn point(center: Point, distance: f64, join: Join) -> Vec<Point> {
+    let c = Vector2::from(&center);
+    match join {
+        Join::Round => circle(c, distance),
+        Join::Miter | Join::Square => [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].iter()
+            .map(|&(x, y)| coordinate(c + Vector2::new(x, y) * distance))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::{Area, Color, Point};
+    use crate::graphic::Path;
+    use crate::offset::{self, Join};
+    use crate::tree::Tree;
+    use crate::polygon;
+
+    fn surface(polygons: &[Vec<Vec<Point>>]) -> f64 {
+        polygons.iter().flatten().map(|r| polygon::signed_area(r)).sum()
+    }
+
+    fn square(size: f64) -> Vec<Vec<Point>> {
+        vec![vec![(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)].into_iter().map(|c| c.into()).collect()]
+    }
+
+    #[test]
+    fn grow() {
+        let miter = offset::polygon(&square(10.0), 1.0, Join::Miter);
+        assert_eq!(1, miter.len());
+        assert_eq!(4, miter[0][0].len());
+        assert_approx_eq!(surface(&miter), 144.0);
+        let square_join = offset::polygon(&square(10.0), 1.0, Join::Square);
+        assert_approx_eq!(surface(&square_join), 144.0);
+        let round = offset::polygon(&square(10.0), 1.0, Join::Round);
+        assert_approx_eq!(surface(&round), 100.0 + 40.0 + PI, 0.05);
+        // a sharp corner is beveled
+        let spike = vec![vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(0.0, 1.0)]];
+        let beveled = offset::polygon(&spike, 1.0, Join::Miter);
+        assert!(beveled[0][0].iter().all(|c| c.x < 12.0));
+    }
+
+    #[test]
+    fn shrink() {
+        let shrunk = offset::polygon(&square(10.0), -1.0, Join::Miter);
+        assert_eq!(1, shrunk.len());
+        assert_approx_eq!(surface(&shrunk), 64.0);
+        assert!(offset::polygon(&square(10.0), -6.0, Join::Miter).is_empty());
+        // the hole grows
+        let mut lawn = square(10.0);
+        lawn.push(vec![(4.0, 4.0), (4.0, 6.0), (6.0, 6.0), (6.0, 4.0)].into_iter().map(|c| c.into()).collect());
+        let shrunk = offset::polygon(&lawn, -1.0, Join::Miter);
+        assert_eq!(2, shrunk[0].len());
+        assert_approx_eq!(surface(&shrunk), 64.0 - 16.0);
+        // an L shape gets a rounded inner corner
+        let l: Vec<Vec<Point>> = vec![vec![(0.0, 0.0), (4.0, 0.0), (4.0, 2.0), (2.0, 2.0), (2.0, 4.0), (0.0, 4.0)]
+            .into_iter().map(|c| c.into()).collect()];
+        let round = offset::polygon(&l, 1.0, Join::Round);
+        let miter = offset::polygon(&l, 1.0, Join::Miter);
+        assert!(surface(&round) < surface(&miter));
+        assert_eq!(offset::polygon(&square(1.0), 0.0, Join::Miter), vec![square(1.0)]);
+    }
+
+    #[test]
+    fn buffer() {
+        let line = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0)];
+        assert_approx_eq!(surface(&offset::polyline(&line, 1.0, Join::Miter)), 20.0);
+        assert_approx_eq!(surface(&offset::polyline(&line, 1.0, Join::Square)), 24.0);
+        assert_approx_eq!(surface(&offset::polyline(&line, 1.0, Join::Round)), 20.0 + PI, 0.05);
+        let corner = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0)];
+        let buffer = offset::polyline(&corner, 1.0, Join::Miter);
+        assert_eq!(1, buffer.len());
+        assert_eq!(6, buffer[0][0].len());
+        assert_approx_eq!(surface(&buffer), 40.0);
+        assert_approx_eq!(polygon::signed_area(&offset::point(Point::new(1.0, 1.0), 2.0, Join::Square)), 16.0);
+        assert_approx_eq!(polygon::signed_area(&offset::point(Point::new(1.0, 1.0), 2.0, Join::Round)),
+                          4.0 * PI, 0.1);
+    }
+
+    #[test]
+    fn elements() {
+        let house = Area::new("house", vec![(0.0, 0.0), (8.0, 0.0), (8.0, 6.0), (0.0, 6.0)]).fill(Color::Red);
+        let strip = house.offset(1.0, Join::Miter);
+        assert_eq!(1, strip.len());
+        assert_eq!("house", strip[0].name);
+        assert_eq!(Color::Red, strip[0].fill);
+        assert_approx_eq!(strip[0].surface(), 80.0);
+        let edge = Path::new("edge", vec![(0.0, 0.0), (10.0, 0.0)]).color(Color::Brown);
+        let buffer = edge.buffer(0.5, Join::Square);
+        assert_eq!(Color::Brown, buffer[0].color);
+        assert_approx_eq!(buffer[0].surface(), 11.0);
+        let tree = Tree {
+            name: "oak".to_string(),
+            species: "Quercus robur".to_string(),
+            trunk_diameter: 0.5,
+            crown_diameter: None,
+            location: Point::new(3.0, 4.0),
+            label_location: Point::new(3.0, 4.0),
+        };
+        let zone = tree.zone(2.0);
+        assert_eq!("oak", zone.name);
+        assert_approx_eq!(zone.surface(), 4.0 * PI, 0.1);
+        assert!(zone.validate().is_ok());
+    }
+}
@@ -0,0 +1,319 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! SVG path data, the `d` attribute of a `path` element.
+
+use std::fmt::Write;
+
+use crate::Point;
+use crate::curve::Curve;
+use crate::error::{Error, Result};
+use crate::util;
+
+/// One command of path data, in absolute coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+    Move(Point),
+    Line(Point),
+    /// A curve ending at the coordinate.
+    Curve(Curve, Point),
+    Close,
+}
+
+/// How path data is written.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PathFormat {
+    /// Use relative commands, `l1,0` instead of `L11,5`.
+    pub relative: bool,
+    /// Use `H` and `V` for horizontal and vertical lines.
+    pub shortcuts: bool,
+}
+
+impl PathFormat {
+    pub fn new() -> PathFormat {
+        PathFormat { relative: false, shortcuts: false }
+    }
+
+    /// Relative commands with `H` and `V` shortcuts, usually the shortest output.
+    pub fn compact() -> PathFormat {
+        PathFormat { relative: true, shortcuts: true }
+    }
+
+    pub fn relative(self) -> Self {
+        PathFormat { relative: true, ..self }
+    }
+
+    pub fn shortcuts(self) -> Self {
+        PathFormat { shortcuts: true, ..self }
+    }
+}
+
+/// Builds path data command by command.
+///
+/// With a precision, coordinates are rounded before relative steps are
+/// computed, so rounding errors do not add up along the path.
+pub struct PathData {
+    format: PathFormat,
+    precision: Option<usize>,
+    data: String,
+    start: (f64, f64),
+    current: (f64, f64),
+}
+
+impl PathData {
+    pub fn new(format: PathFormat, precision: Option<usize>) -> PathData {
+        PathData { format, precision, data: String::new(), start: (0.0, 0.0), current: (0.0, 0.0) }
+    }
+
+    fn round(&self, v: f64) -> f64 {
+        match self.precision {
+            Some(p) => {
+                let factor = 10f64.powi(p as i32);
+                (v * factor).round() / factor
+            }
+            None => v,
+        }
+    }
+
+    fn number(&self, v: f64) -> String {
+        match self.precision {
+            Some(p) => util::format_number(v, p),
+            // avoid writing -0
+            None if v == 0.0 => "0".to_string(),
+            None => v.to_string(),
+        }
+    }
+
+    fn command(&mut self, command: char, args: &[f64]) {
+        if !self.data.is_empty() {
+            self.data.push(' ');
+        }
+        self.data.push(command);
+        let args: Vec<String> = args.iter().map(|&a| self.number(a)).collect();
+        write!(&mut self.data, "{}", args.join(",")).unwrap();
+    }
+
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        let (x, y) = (self.round(x), self.round(y));
+        if self.format.relative && !self.data.is_empty() {
+            let (cx, cy) = self.current;
+            self.command('m', &[x - cx, y - cy]);
+        } else {
+            self.command('M', &[x, y]);
+        }
+        self.start = (x, y);
+        self.current = (x, y);
+    }
+
+    pub fn line_to(&mut self, x: f64, y: f64) {
+        let (x, y) = (self.round(x), self.round(y));
+        let (cx, cy) = self.current;
+        let relative = self.format.relative;
+        if self.format.shortcuts && y == cy {
+            if relative { self.command('h', &[x - cx]) } else { self.command('H', &[x]) }
+        } else if self.format.shortcuts && x == cx {
+            if relative { self.command('v', &[y - cy]) } else { self.command('V', &[y]) }
+        } else if relative {
+            self.command('l', &[x - cx, y - cy]);
+        } else {
+            self.command('L', &[x, y]);
+        }
+        self.current = (x, y);
+    }
+
+    /// A Bezier curve or arc from the current point to `x`,`y`.
+    pub fn curve_to(&mut self, curve: &Curve, x: f64, y: f64) {
+        let (x, y) = (self.round(x), self.round(y));
+        let (cx, cy) = self.current;
+        let (ox, oy) = if self.format.relative { (cx, cy) } else { (0.0, 0.0) };
+        let relative = |c: char| if self.format.relative { c.to_ascii_lowercase() } else { c };
+        match *curve {
+            Curve::Quadratic(c) => {
+                let command = relative('Q');
+                self.command(command, &[self.round(c.x) - ox, self.round(c.y) - oy, x - ox, y - oy]);
+            }
+            Curve::Cubic(c1, c2) => {
+                let command = relative('C');
+                self.command(command, &[self.round(c1.x) - ox, self.round(c1.y) - oy,
+                    self.round(c2.x) - ox, self.round(c2.y) - oy, x - ox, y - oy]);
+            }
+            Curve::Arc { rx, ry, rotation, large_arc, sweep } => {
+                let command = relative('A');
+                let flag = |f: bool| if f { 1.0 } else { 0.0 };
+                self.command(command, &[rx, ry, rotation, flag(large_arc), flag(sweep), x - ox, y - oy]);
+            }
+        }
+        self.current = (x, y);
+    }
+
+    pub fn close(&mut self) {
+        self.command('z', &[]);
+        self.current = self.start;
+    }
+
+    pub fn push(&mut self, segment: &Segment) {
+        match segment {
+            Segment::Move(c) => self.move_to(c.x, c.y),
+            Segment::Line(c) => self.line_to(c.x, c.y),
+            Segment::Curve(curve, c) => self.curve_to(curve, c.x, c.y),
+            Segment::Close => self.close(),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.data
+    }
+}
+
+/// Path data for `segments`.
+pub fn format(segments: &[Segment], format: PathFormat, precision: Option<usize>) -> String {
+    let mut data = PathData::new(format, precision);
+    segments.iter().for_each(|s| data.push(s));
+    data.finish()
+}
+
+enum Token {
+    Command(char),
+    Number(f64),
+}
+
+fn tokens(d: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = d.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() || c == ',' {
+            chars.next();
+        } else if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            tokens.push(Token::Command(c));
+            chars.next();
+        } else {
+            let mut end = i;
+            let mut previous = ' ';
+            while let Some(&(j, c)) = chars.peek() {
+                let sign = (c == '-' || c == '+') && (j == i || previous == 'e' || previous == 'E');
+                if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || sign) {
+                    break;
+                }
+                end = j + c.len_utf8();
+                previous = c;
+                chars.next();
+            }
+            let number = d[i..end].parse().map_err(|_| Error::Parse(format!("invalid path data '{}'", d)))?;
+            tokens.push(Token::Number(number));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parse path data made of `M`, `L`, `H`, `V`, `Q`, `C`, `A` and `Z`
+/// commands, absolute or relative.
+pub fn parse(d: &str) -> Result<Vec<Segment>> {
+    let invalid = || Error::Parse(format!("invalid path data '{}'", d));
+    let tokens = tokens(d)?;
+    let mut segments = vec![];
+    let mut start = Point::new(0.0, 0.0);
+    let mut current = start;
+    let mut command = None;
+    let mut i = 0;
+    let next = |i: &mut usize| match tokens.get(*i) {
+        Some(Token::Number(n)) => {
+            *i += 1;
+            Ok(*n)
+        }
+        _ => Err(invalid()),
+    };
+    while i < tokens.len() {
+        if let Token::Command(c) = tokens[i] {
+            command = Some(c);
+            i += 1;
+        }
+        let c = command.ok_or_else(invalid)?;
+        let (ox, oy) = if c.is_ascii_lowercase() { (current.x, current.y) } else { (0.0, 0.0) };
+        match c.to_ascii_uppercase() {
+            'M' => {
+                current = Point::new(ox + next(&mut i)?, oy + next(&mut i)?);
+                start = current;
+                segments.push(Segment::Move(current));
+                // further coordinate pairs are lines
+                command = Some(if c == 'm' { 'l' } else { 'L' });
+            }
+            'L' => {
+                current = Point::new(ox + next(&mut i)?, oy + next(&mut i)?);
+                segments.push(Segment::Line(current));
+            }
+            'H' => {
+                current = Point::new(ox + next(&mut i)?, current.y);
+                segments.push(Segment::Line(current));
+            }
+            'V' => {
+                current = Point::new(current.x, oy + next(&mut i)?);
+                segments.push(Segment::Line(current));
+            }
+            'Q' => {
+                let control = Point::new(ox + next(&mut i)?, oy + next(&mut i)?);
+                current = Point::new(ox + next(&mut i)?, oy + next(&mut i)?);
+                segments.push(Segment::Curve(Curve::Quadratic(control), current));
+            }
+            'C' => {
+                let c1 = Point::new(ox + next(&mut i)?, oy + next(&mut i)?);
+                let c2 = Point::new(ox + next(&mut i)?, oy + next(&mut i)?);
+                current = Point::new(ox + next(&mut i)?, oy + next(&mut i)?);
+                segments.push(Segment::Curve(Curve::Cubic(c1, c2), current));
+            }
+            'A' => {
+                let (rx, ry, rotation) = (next(&mut i)?, next(&mut i)?, next(&mut i)?);
+                let (large_arc, sweep) = (next(&mut i)? != 0.0, next(&mut i)? != 0.0);
+                current = Point::new(ox + next(&mut i)?, oy + next(&mut i)?);
+                segments.push(Segment::Curve(Curve::Arc { rx, ry, rotation, large_arc, sweep }, current));
+            }
+            'Z' => {
+                current = start;
+                segments.push(Segment::Close);
+                command = None;
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Point;
+    use crate::curve::Curve;
+    use crate::path_data::{self, PathFormat, Segment};
+
+    fn square() -> Vec<Segment> {
+        vec![
+            Segment::Move(Point::new(10.0, 5.0)),
+            Segment::Line(Point::new(12.0, 5.0)),
+            Segment::Line(Point::new(12.0, 7.0)),
+            Segment::Line(Point::new(10.1 + 0.2, 7.5)),
+            Segment::Curve(Curve::Quadratic(Point::new(9.0, 7.0)), Point::new(10.0, 6.0)),
+            Segment::Curve(Curve::Arc { rx: 1.0, ry: 0.5, rotation: 30.0, large_arc: false, sweep: true },
+                           Point::new(10.0, 5.0)),
+            Segment::Close,
+        ]
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!("M10,5 L12,5 L12,7 L10.3,7.5 Q9,7,10,6 A1,0.5,30,0,1,10,5 z",
+                   path_data::format(&square(), PathFormat::new(), Some(3)));
+        assert_eq!("M10,5 H12 V7 L10.3,7.5 Q9,7,10,6 A1,0.5,30,0,1,10,5 z",
+                   path_data::format(&square(), PathFormat::new().shortcuts(), Some(3)));
+        assert_eq!("M10,5 h2 v2 l-1.7,0.5 q-1.3,-0.5,-0.3,-1.5 a1,0.5,30,0,1,0,-1 z",
+                   path_data::format(&square(), PathFormat::compact(), Some(3)));
+        assert!(path_data::format(&square(), PathFormat::new(), None).contains("10.299999999999999"));
+    }
+
+    #[test]
+    fn round_trip() {
+        for format in &[PathFormat::new(), PathFormat::new().relative(), PathFormat::compact()] {
+            let d = path_data::format(&square(), *format, Some(3));
+            let parsed = path_data::parse(&d).unwrap();
+            assert_eq!(d, path_data::format(&parsed, *format, Some(3)));
+        }
+        assert_eq!(2, path_data::parse("M1-2 3e1,4").unwrap().len());
+        assert!(path_data::parse("M1 L").is_err());
+    }
+}
@@ -0,0 +1,357 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! PDF output at a real-world print scale.
+//!
+//! Plans that do not fit on one sheet are tiled over multiple pages.
+
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::{Color, Document};
+use crate::backend::{self, Backend};
+use crate::error::{self, Error, Result};
+use crate::graphic::World;
+use crate::matrix3::Matrix3;
+use crate::vector2::Vector2;
+
+/// Points per millimetre.
+const MM: f64 = 72.0 / 25.4;
+
+/// Most pages a document is tiled over, against scales that are far too large.
+pub const MAX_PAGES: usize = 10_000;
+
+/// Control point distance for approximating a quarter circle with a cubic Bezier.
+const KAPPA: f64 = 0.552_284_749_8;
+
+/// Paper size, in portrait orientation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Paper {
+    A4,
+    A3,
+    /// Width and height in millimetre.
+    Custom(f64, f64),
+}
+
+impl Paper {
+    /// Width and height in millimetre.
+    pub fn size(&self) -> (f64, f64) {
+        match *self {
+            Paper::A4 => (210.0, 297.0),
+            Paper::A3 => (297.0, 420.0),
+            Paper::Custom(w, h) => (w, h),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfOptions {
+    pub paper: Paper,
+    pub landscape: bool,
+    /// Denominator of the print scale, 100.0 for 1:100.
+    pub scale: f64,
+    /// Margin around the printed area in millimetre.
+    pub margin: f64,
+    /// Length of one plan unit in metre.
+    pub unit: f64,
+}
+
+impl PdfOptions {
+    pub fn new(paper: Paper, scale: f64) -> PdfOptions {
+        PdfOptions { paper, landscape: false, scale, margin: 10.0, unit: 1.0 }
+    }
+
+    pub fn landscape(self) -> Self {
+        PdfOptions { landscape: true, ..self }
+    }
+
+    pub fn margin(self, margin: f64) -> Self {
+        PdfOptions { margin, ..self }
+    }
+
+    pub fn unit(self, unit: f64) -> Self {
+        PdfOptions { unit, ..self }
+    }
+
+    /// Check that the scale, unit and paper are positive and the margins
+    /// leave a printable area.
+    pub fn validate(&self) -> Result<()> {
+        let element = "PDF output";
+        error::check_positive(element, "scale", self.scale)?;
+        error::check_positive(element, "unit", self.unit)?;
+        let (w, h) = self.paper.size();
+        error::check_positive(element, "paper width", w)?;
+        error::check_positive(element, "paper height", h)?;
+        error::check_size(element, "margin", self.margin)?;
+        let (aw, ah) = self.printable_size();
+        error::check_positive(element, "printable width", aw)?;
+        error::check_positive(element, "printable height", ah)
+    }
+
+    /// Page width and height in points.
+    fn page_size(&self) -> (f64, f64) {
+        let (w, h) = self.paper.size();
+        if self.landscape {
+            (h * MM, w * MM)
+        } else {
+            (w * MM, h * MM)
+        }
+    }
+
+    /// Width and height of the page inside the margins in points.
+    fn printable_size(&self) -> (f64, f64) {
+        let (pw, ph) = self.page_size();
+        (pw - 2.0 * self.margin * MM, ph - 2.0 * self.margin * MM)
+    }
+
+    /// Points per plan unit.
+    fn points_per_unit(&self) -> f64 {
+        self.unit * 1000.0 / self.scale * MM
+    }
+}
+
+/// PDF content stream operators for one drawing.
+struct Content(String);
+
+fn num(v: f64) -> String {
+    let s = format!("{:.3}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" { "0".into() } else { s.into() }
+}
+
+fn escape(text: &str) -> String {
+    text.chars().map(|c| match c {
+        '(' | ')' | '\\' => format!("\\{}", c),
+        c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+        _ => "?".into(),
+    }).collect()
+}
+
+impl Content {
+    fn color(&mut self, color: Color, op: &str) -> bool {
+        match color.rgb() {
+            Some((r, g, b)) => {
+                writeln!(self.0, "{} {} {} {}", num(r as f64 / 255.0), num(g as f64 / 255.0), num(b as f64 / 255.0), op).unwrap();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn points(&mut self, points: &[(f64, f64)]) {
+        for (i, &(x, y)) in points.iter().enumerate() {
+            writeln!(self.0, "{} {} {}", num(x), num(y), if i == 0 { "m" } else { "l" }).unwrap();
+        }
+    }
+
+    fn paint(&mut self, stroke: Color, fill: Color, stroke_width: f64) {
+        let stroke = self.color(stroke, "RG");
+        let fill = self.color(fill, "rg");
+        if stroke {
+            writeln!(self.0, "{} w [] 0 d", num(stroke_width)).unwrap();
+        }
+        let op = match (stroke, fill) {
+            (true, true) => "B",
+            (true, false) => "S",
+            (false, true) => "f",
+            (false, false) => "n",
+        };
+        writeln!(self.0, "{}", op).unwrap();
+    }
+}
+
+impl Backend for Content {
+    fn polygon(&mut self, rings: &[Vec<(f64, f64)>], stroke: Color, fill: Color, stroke_width: f64) {
+        for ring in rings {
+            self.points(ring);
+            self.0.push_str("h\n");
+        }
+        self.paint(stroke, fill, stroke_width);
+    }
+
+    fn polyline(&mut self, points: &[(f64, f64)], stroke: Color, stroke_width: f64, dash: &[f64]) {
+        if !self.color(stroke, "RG") {
+            return;
+        }
+        let dash: Vec<String> = dash.iter().map(|d| num(*d)).collect();
+        writeln!(self.0, "{} w [{}] 0 d", num(stroke_width), dash.join(" ")).unwrap();
+        self.points(points);
+        self.0.push_str("S\n");
+    }
+
+    fn circle(&mut self, (cx, cy): (f64, f64), r: f64, stroke: Color, fill: Color, stroke_width: f64) {
+        let k = r * KAPPA;
+        writeln!(self.0, "{} {} m", num(cx + r), num(cy)).unwrap();
+        let quarters = [
+            (cx + r, cy + k, cx + k, cy + r, cx, cy + r),
+            (cx - k, cy + r, cx - r, cy + k, cx - r, cy),
+            (cx - r, cy - k, cx - k, cy - r, cx, cy - r),
+            (cx + k, cy - r, cx + r, cy - k, cx + r, cy),
+        ];
+        for &(x1, y1, x2, y2, x3, y3) in &quarters {
+            writeln!(self.0, "{} {} {} {} {} {} c", num(x1), num(y1), num(x2), num(y2), num(x3), num(y3)).unwrap();
+        }
+        self.0.push_str("h\n");
+        self.paint(stroke, fill, stroke_width);
+    }
+
+    /// Helvetica has an average glyph width of about 0.55 of the font size.
+    fn text(&mut self, (x, y): (f64, f64), size: f64, text: &str) {
+        let width = text.chars().count() as f64 * size * 0.55;
+        writeln!(self.0, "0 g BT /F1 {} Tf {} {} Td ({}) Tj ET", num(size), num(x - width / 2.0), num(y), escape(text)).unwrap();
+    }
+}
+
+/// Number of pages horizontally and vertically needed for the document.
+///
+/// Fails when the options are invalid or more than `MAX_PAGES` are needed.
+pub fn tiles(document: &Document, options: &PdfOptions) -> Result<(usize, usize)> {
+    options.validate()?;
+    let view = document.view_box();
+    let k = options.points_per_unit();
+    let (aw, ah) = options.printable_size();
+    // allow for rounding so an exact fit stays on one page
+    let count = |content: f64, available: f64| (content / available - 1e-9).ceil().max(1.0);
+    let (nx, ny) = (count(view.width() * k, aw), count(view.height() * k, ah));
+    let pages = nx * ny;
+    if !pages.is_finite() || pages > MAX_PAGES as f64 {
+        return Err(Error::OutOfRange { element: "PDF output".to_string(), field: "page count".to_string() });
+    }
+    Ok((nx as usize, ny as usize))
+}
+
+/// Write the document as PDF.
+///
+/// The drawing is a single form XObject that every page clips and places,
+/// so tiling does not repeat it. Fails when the options are invalid, see
+/// `PdfOptions::validate` and `tiles`.
+pub fn write_pdf<W: Write>(document: &Document, options: &PdfOptions, mut out: W) -> Result<()> {
+    let (nx, ny) = tiles(document, options)?;
+    let view = document.view_box();
+    let k = options.points_per_unit();
+    let (pw, ph) = options.page_size();
+    let margin = options.margin * MM;
+    let (aw, ah) = options.printable_size();
+    let (width, height) = (view.width() * k, view.height() * k);
+
+    // plan -> SVG -> points with the origin at the bottom left of the view box
+    let matrix = World::new("Document", document.origin).matrix()
+        * Matrix3::translate(Vector2::new(-view.min_x, -view.max_y))
+        * Matrix3::scale(k, -k);
+    let mut content = Content(String::new());
+    content.0.push_str("1 j\n");
+    backend::draw(document, &matrix, &mut content);
+
+    let fonts = "/Font << /F1 3 0 R >>";
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        String::new(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+        format!("<< /Type /XObject /Subtype /Form /BBox [0 0 {} {}] /Resources << {} >> /Length {} >>\n\
+                 stream\n{}endstream", num(width), num(height), fonts, content.0.len(), content.0),
+    ];
+    let mut kids = vec![];
+    for j in 0..ny {
+        for i in 0..nx {
+            let tx = if nx == 1 { (pw - width) / 2.0 } else { margin - i as f64 * aw };
+            let ty = if ny == 1 { (ph - height) / 2.0 } else { margin - (height - (j + 1) as f64 * ah) };
+            let mut stream = String::new();
+            writeln!(stream, "q {} {} {} {} re W n", num(margin), num(margin), num(aw), num(ah)).unwrap();
+            writeln!(stream, "1 0 0 1 {} {} cm /Drawing Do Q", num(tx), num(ty)).unwrap();
+            let caption = format!("1:{} page {}/{}", options.scale, j * nx + i + 1, nx * ny);
+            writeln!(stream, "0 g BT /F1 8 Tf {} {} Td ({}) Tj ET", num(margin), num(margin / 2.0), escape(&caption)).unwrap();
+            objects.push(format!("<< /Length {} >>\nstream\n{}endstream", stream.len(), stream));
+            let content_id = objects.len();
+            objects.push(format!("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents {} 0 R \
+                                  /Resources << {} /XObject << /Drawing 4 0 R >> >> >>",
+                                 num(pw), num(ph), content_id, fonts));
+            kids.push(format!("{} 0 R", objects.len()));
+        }
+    }
+    objects[1] = format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), kids.len());
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object).unwrap();
+    }
+    let xref = pdf.len();
+    write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).unwrap();
+    for offset in offsets {
+        writeln!(pdf, "{:010} 00000 n ", offset).unwrap();
+    }
+    write!(pdf, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).unwrap();
+    out.write_all(pdf.as_bytes())?;
+    Ok(())
+}
+
+impl Document {
+    /// Write the document as PDF to `filename`, see `write_pdf`.
+    pub fn save_pdf(&self, filename: &str, options: &PdfOptions) -> Result<()> {
+        options.validate()?;
+        let mut out = BufWriter::new(File::create(filename)?);
+        write_pdf(self, options, &mut out)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Area, Document, Error};
+    use crate::pdf::{self, Paper, PdfOptions};
+
+    fn plan() -> Document {
+        let mut doc = Document::new(0.0, 0.0, 40.0, 30.0, 10);
+        doc.add(Area::new("plot", vec![(0.0, 0.0), (40.0, 0.0), (40.0, 30.0), (0.0, 30.0)]));
+        doc
+    }
+
+    #[test]
+    fn single_page() {
+        let options = PdfOptions::new(Paper::A4, 200.0).landscape();
+        assert_eq!((1, 1), pdf::tiles(&plan(), &options).unwrap());
+        let mut out = vec![];
+        pdf::write_pdf(&plan(), &options, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("/Count 1 "));
+        assert!(text.ends_with("%%EOF\n"));
+    }
+
+    #[test]
+    fn tiled() {
+        // 400 mm x 300 mm on a printable area of 190 mm x 277 mm
+        let options = PdfOptions::new(Paper::A4, 100.0);
+        assert_eq!((3, 2), pdf::tiles(&plan(), &options).unwrap());
+        let mut out = vec![];
+        pdf::write_pdf(&plan(), &options, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("/Count 6 "));
+        // the drawing is written once and placed on every page
+        assert_eq!(1, text.matches("1 j\n").count());
+        assert_eq!(6, text.matches("/Drawing Do").count());
+    }
+
+    #[test]
+    fn invalid_options() {
+        let invalid = |options: PdfOptions| {
+            assert!(options.validate().is_err());
+            assert!(pdf::tiles(&plan(), &options).is_err());
+            assert!(pdf::write_pdf(&plan(), &options, vec![]).is_err());
+        };
+        invalid(PdfOptions::new(Paper::A4, 0.0));
+        invalid(PdfOptions::new(Paper::A4, -100.0));
+        invalid(PdfOptions::new(Paper::A4, f64::NAN));
+        invalid(PdfOptions::new(Paper::A4, 100.0).unit(0.0));
+        invalid(PdfOptions::new(Paper::A4, 100.0).margin(105.0));
+        invalid(PdfOptions::new(Paper::Custom(0.0, 100.0), 100.0));
+        match pdf::tiles(&plan(), &PdfOptions::new(Paper::A4, 1e-300)) {
+            Err(Error::OutOfRange { field, .. }) => assert_eq!("page count", field),
+            r => panic!("unexpected {:?}", r),
+        }
+        assert_eq!("PDF output has an out of range scale",
+                   PdfOptions::new(Paper::A4, 0.0).validate().unwrap_err().to_string());
+    }
+}
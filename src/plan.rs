@@ -0,0 +1,420 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! Declarative plan descriptions in TOML, JSON or YAML.
+//!
+//! A plan file describes the document extents and its areas, paths, trees,
+//! labels and worlds; `Plan::document` builds the `Document` from it.
+//!
+//! Each kind is a separate list, so the order between kinds is not kept:
+//! in the document and in every world the elements are drawn bottom to top
+//! as areas, paths, trees, labels and then the nested worlds, each list in
+//! file order.
+//!
+//! ```toml
+//! [document]
+//! width = 40.0
+//! height = 30.0
+//!
+//! [[areas]]
+//! name = "lawn"
+//! corners = [[0.0, 0.0], [20.0, 0.0], [20.0, 10.0], [0.0, 10.0]]
+//! fill = "lightgreen"
+//!
+//! [[worlds]]
+//! name = "orchard"
+//! origin = [25.0, 5.0]
+//! rotation = 15.0
+//!
+//! [[worlds.trees]]
+//! name = "apple"
+//! location = [2.0, 3.0]
+//! trunk_diameter = 0.2
+//! crown_diameter = 3.0
+//! ```
+
+use std::fs;
+use std::io;
+use std::path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Area, Color, Document, Frame, Label};
+use crate::element::Element;
+use crate::error::{Error, Result};
+use crate::graphic::{Path, Ring, World};
+use crate::path_data::PathFormat;
+use crate::tree::Tree;
+
+fn default_pixels_per_unit() -> i64 {
+    10
+}
+
+fn default_margin() -> f64 {
+    1.0
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_show_axis() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DocumentSpec {
+    #[serde(default)]
+    pub min_x: f64,
+    #[serde(default)]
+    pub min_y: f64,
+    /// Without width and height the document is fitted to its content; giving
+    /// only one of them is an error.
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    #[serde(default = "default_pixels_per_unit")]
+    pub pixels_per_unit: i64,
+    /// Margin used when fitting the document to its content.
+    #[serde(default = "default_margin")]
+    pub margin: f64,
+    /// Decimal places of numbers in the SVG output.
+    pub precision: Option<usize>,
+    /// Write SVG path data with relative commands and `H`/`V` shortcuts.
+    #[serde(default)]
+    pub compact_paths: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AreaSpec {
+    pub name: String,
+    pub corners: Vec<(f64, f64)>,
+    /// Corners of the parts cut out of the area.
+    #[serde(default)]
+    pub holes: Vec<Vec<(f64, f64)>>,
+    pub color: Option<Color>,
+    pub fill: Option<Color>,
+    pub stroke_width: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PathSpec {
+    pub name: String,
+    pub points: Vec<(f64, f64)>,
+    pub color: Option<Color>,
+    pub stroke_width: Option<f64>,
+    pub stroke_dash: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TreeSpec {
+    pub name: String,
+    #[serde(default)]
+    pub species: String,
+    pub trunk_diameter: f64,
+    pub crown_diameter: Option<f64>,
+    pub location: (f64, f64),
+    /// Defaults to the location of the tree.
+    pub label_location: Option<(f64, f64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LabelSpec {
+    pub text: String,
+    pub location: (f64, f64),
+    pub size: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorldSpec {
+    pub name: String,
+    pub origin: (f64, f64),
+    /// Rotation in degrees.
+    #[serde(default)]
+    pub rotation: f64,
+    #[serde(default)]
+    pub flip_x: bool,
+    #[serde(default)]
+    pub flip_y: bool,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    pub axis_scale: Option<f64>,
+    #[serde(default = "default_show_axis")]
+    pub show_axis: bool,
+    pub stroke_width: Option<f64>,
+    #[serde(default)]
+    pub areas: Vec<AreaSpec>,
+    #[serde(default)]
+    pub paths: Vec<PathSpec>,
+    #[serde(default)]
+    pub trees: Vec<TreeSpec>,
+    #[serde(default)]
+    pub labels: Vec<LabelSpec>,
+    /// Worlds nested in this one, placed in its coordinates.
+    #[serde(default)]
+    pub worlds: Vec<WorldSpec>,
+}
+
+/// A complete plan description.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Plan {
+    pub document: DocumentSpec,
+    #[serde(default)]
+    pub areas: Vec<AreaSpec>,
+    #[serde(default)]
+    pub paths: Vec<PathSpec>,
+    #[serde(default)]
+    pub trees: Vec<TreeSpec>,
+    #[serde(default)]
+    pub labels: Vec<LabelSpec>,
+    #[serde(default)]
+    pub worlds: Vec<WorldSpec>,
+}
+
+impl AreaSpec {
+    pub fn area(&self) -> Area {
+        Area {
+            color: self.color.unwrap_or(Color::Black),
+            fill: self.fill.unwrap_or(Color::None),
+            stroke_width: self.stroke_width,
+            holes: self.holes.iter().map(|h| Ring::new(h.clone())).collect(),
+            ..Area::new(&self.name, self.corners.clone())
+        }
+    }
+}
+
+impl PathSpec {
+    pub fn path(&self) -> Path {
+        Path {
+            color: self.color.unwrap_or(Color::Black),
+            stroke_width: self.stroke_width,
+            stroke_dash: self.stroke_dash.clone(),
+            ..Path::new(&self.name, self.points.clone())
+        }
+    }
+}
+
+impl TreeSpec {
+    pub fn tree(&self) -> Tree {
+        Tree {
+            name: self.name.clone(),
+            species: self.species.clone(),
+            trunk_diameter: self.trunk_diameter,
+            crown_diameter: self.crown_diameter,
+            location: self.location.into(),
+            label_location: self.label_location.unwrap_or(self.location).into(),
+        }
+    }
+}
+
+impl LabelSpec {
+    pub fn label(&self) -> Label {
+        Label { size: self.size, ..Label::new(self.location.into(), &self.text) }
+    }
+}
+
+fn elements(areas: &[AreaSpec], paths: &[PathSpec], trees: &[TreeSpec],
+            labels: &[LabelSpec], worlds: &[WorldSpec]) -> Vec<Element> {
+    areas.iter().map(|a| a.area().into())
+        .chain(paths.iter().map(|p| p.path().into()))
+        .chain(trees.iter().map(|t| t.tree().into()))
+        .chain(labels.iter().map(|l| l.label().into()))
+        .chain(worlds.iter().map(|w| w.world().into()))
+        .collect()
+}
+
+impl WorldSpec {
+    pub fn world(&self) -> World {
+        let location = Frame::new(self.origin.0, self.origin.1)
+            .rotate(self.rotation)
+            .flip_x_if(self.flip_x)
+            .flip_y_if(self.flip_y)
+            .scale_x(self.scale)
+            .scale_y(self.scale);
+        let mut world = World::new(&self.name, location)
+            .show_axis(self.show_axis)
+            .stroke_width_opt(self.stroke_width);
+        if let Some(axis_scale) = self.axis_scale {
+            world = world.axis_scale(axis_scale);
+        }
+        world.add_all(elements(&self.areas, &self.paths, &self.trees, &self.labels, &self.worlds));
+        world
+    }
+}
+
+fn invalid<E: ToString>(e: E) -> Error {
+    Error::Parse(e.to_string())
+}
+
+impl Plan {
+    pub fn from_toml(s: &str) -> Result<Plan> {
+        toml::from_str(s).map_err(invalid)
+    }
+
+    pub fn from_json(s: &str) -> Result<Plan> {
+        serde_json::from_str(s).map_err(invalid)
+    }
+
+    pub fn from_yaml(s: &str) -> Result<Plan> {
+        serde_yaml::from_str(s).map_err(invalid)
+    }
+
+    /// Load a plan, the format is chosen by the extension of `filename`.
+    pub fn load(filename: &str) -> Result<Plan> {
+        let data = fs::read_to_string(filename)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", filename, e)))?;
+        let extension = path::Path::new(filename).extension().and_then(|e| e.to_str()).unwrap_or("");
+        let plan = match extension {
+            "toml" => Plan::from_toml(&data),
+            "json" => Plan::from_json(&data),
+            "yaml" | "yml" => Plan::from_yaml(&data),
+            _ => Err(invalid(format!("unknown plan format '{}'", extension))),
+        };
+        plan.map_err(|e| invalid(format!("{}: {}", filename, e)))
+    }
+
+    /// Build the document described by the plan.
+    ///
+    /// Fails when only one of the document width and height is given, or
+    /// when an element has degenerate or non-finite geometry.
+    pub fn document(&self) -> Result<Document> {
+        let d = &self.document;
+        let mut document = match (d.width, d.height) {
+            (Some(width), Some(height)) => Document::new(d.min_x, d.min_y, width, height, d.pixels_per_unit),
+            (None, None) => Document::auto_fit(d.pixels_per_unit, d.margin),
+            (Some(_), None) => return Err(invalid("document has a width but no height")),
+            (None, Some(_)) => return Err(invalid("document has a height but no width")),
+        };
+        document.precision = d.precision;
+        if d.compact_paths {
+            document.path_format = PathFormat::compact();
+        }
+        for e in elements(&self.areas, &self.paths, &self.trees, &self.labels, &self.worlds) {
+            document.add(e);
+        }
+        document.validate()?;
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::{Color, Element, Point};
+    use crate::error::Error;
+    use crate::plan::Plan;
+
+    const TOML: &str = r#"
+[document]
+width = 40.0
+height = 30.0
+
+[[areas]]
+name = "lawn"
+corners = [[0.0, 0.0], [20.0, 0.0], [20.0, 10.0], [0.0, 10.0]]
+fill = "lightgreen"
+
+[[worlds]]
+name = "orchard"
+origin = [25.0, 5.0]
+rotation = 15.0
+
+[[worlds.trees]]
+name = "apple"
+location = [2.0, 3.0]
+trunk_diameter = 0.2
+crown_diameter = 3.0
+"#;
+
+    #[test]
+    fn toml() {
+        let plan = Plan::from_toml(TOML).unwrap();
+        let doc = plan.document().unwrap();
+        assert_eq!(40.0, doc.width);
+        match doc.find("lawn") {
+            Some(Element::Area(a)) => assert_eq!(Color::LightGreen, a.fill),
+            _ => panic!("lawn not found"),
+        }
+        match doc.find("apple") {
+            Some(Element::Tree(t)) => assert_eq!(t.location, t.label_location),
+            _ => panic!("apple not found"),
+        }
+    }
+
+    #[test]
+    fn formats_agree() {
+        let plan = Plan::from_toml(TOML).unwrap();
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(plan, Plan::from_json(&json).unwrap());
+        let yaml = serde_yaml::to_string(&plan).unwrap();
+        assert_eq!(plan, Plan::from_yaml(&yaml).unwrap());
+    }
+
+    #[test]
+    fn nested_worlds() {
+        let plan = Plan::from_yaml(r#"
+document: { width: 40.0, height: 30.0 }
+worlds:
+  - name: site
+    origin: [10.0, 0.0]
+    rotation: 90.0
+    worlds:
+      - name: building
+        origin: [2.0, 0.0]
+        show_axis: false
+        trees: [{ name: fig, location: [1.0, 1.0], trunk_diameter: 0.2 }]
+"#).unwrap();
+        let doc = plan.document().unwrap();
+        assert_eq!(2, doc.frames("fig").unwrap().len());
+        let fig = doc.absolute("fig", Point::new(1.0, 1.0)).unwrap();
+        assert_approx_eq!(fig.x, 9.0);
+        assert_approx_eq!(fig.y, 3.0);
+        match doc.find("building") {
+            Some(Element::World(w)) => assert!(!w.show_axis),
+            _ => panic!("building not found"),
+        }
+    }
+
+    #[test]
+    fn partial_extents() {
+        let plan = Plan::from_yaml("document: { width: 40.0 }\n").unwrap();
+        match plan.document() {
+            Err(Error::Parse(message)) => assert_eq!("document has a width but no height", message),
+            r => panic!("unexpected {:?}", r),
+        }
+        let plan = Plan::from_toml("[document]\nheight = 30.0\n").unwrap();
+        assert!(matches!(plan.document(), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn missing_file() {
+        match Plan::load("/nonexistent/garden.toml") {
+            Err(Error::Io(e)) => assert!(e.to_string().starts_with("/nonexistent/garden.toml: "), "{}", e),
+            r => panic!("unexpected {:?}", r.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn auto_fit() {
+        let plan = Plan::from_yaml("document: { margin: 2.0 }\nlabels: [{ text: hi, location: [1.0, 1.0] }]\n").unwrap();
+        assert_eq!(Some(2.0), plan.document().unwrap().fit_margin);
+    }
+
+    #[test]
+    fn unknown_field() {
+        assert!(Plan::from_toml("[document]\nwidht = 3.0\n").is_err());
+    }
+
+    #[test]
+    fn degenerate_area() {
+        let plan = Plan::from_toml("[document]\n[[areas]]\nname = \"strip\"\ncorners = [[0.0, 0.0], [1.0, 0.0]]\n").unwrap();
+        let e = plan.document().unwrap_err();
+        assert_eq!("area 'strip' has 2 points, at least 3 are needed", e.to_string());
+    }
+}
@@ -0,0 +1,201 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+use std::ops;
+
+use crate::frame::Frame;
+use crate::matrix3::Matrix3;
+use crate::vector2::Vector2;
+use crate::vector3::Vector3;
+
+/// A position in a plane, the corner of an area or a point of a path.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub const fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    pub const fn tup(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    pub fn translate(self, x: f64, y: f64) -> Point {
+        Point::new(self.x + x, self.y + y)
+    }
+
+    pub fn transform(self, matrix: &Matrix3) -> Point {
+        let v = Vector3::new(self.x, self.y, 1.0) * *matrix;
+        Point::new(v.x, v.y)
+    }
+
+    /// The position of this reference point in the world placed at `world`.
+    pub fn to_world(self, world: &Frame) -> Point {
+        self.transform(&world.matrix().inverse())
+    }
+
+    /// The position in the reference of this point in the world placed at
+    /// `world`, the inverse of `to_world`.
+    pub fn from_world(self, world: &Frame) -> Point {
+        self.transform(&world.matrix())
+    }
+
+    pub fn distance(self, other: Point) -> f64 {
+        (other - self).length()
+    }
+}
+
+impl From<(f64, f64)> for Point {
+    fn from(t: (f64, f64)) -> Point {
+        Point::new(t.0, t.1)
+    }
+}
+
+impl From<Vector2> for Point {
+    fn from(v: Vector2) -> Point {
+        Point::new(v.x, v.y)
+    }
+}
+
+impl ops::Add<Vector2> for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Vector2) -> Point {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl ops::AddAssign<Vector2> for Point {
+    fn add_assign(&mut self, rhs: Vector2) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::Sub<Vector2> for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Vector2) -> Point {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl ops::SubAssign<Vector2> for Point {
+    fn sub_assign(&mut self, rhs: Vector2) {
+        *self = *self - rhs;
+    }
+}
+
+/// The vector from `rhs` to `self`.
+impl ops::Sub<Point> for Point {
+    type Output = Vector2;
+
+    fn sub(self, rhs: Point) -> Vector2 {
+        Vector2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+pub struct Points;
+
+impl Points {
+    /// A tenth of the smallest extent of `points`, 0 when there are none.
+    ///
+    /// NaN coordinates are ignored.
+    pub fn axis_scale(points: &[Point]) -> f64 {
+        if points.is_empty() {
+            return 0.0;
+        }
+        let min_x = points.iter().map(|c| c.x).fold(f64::INFINITY, f64::min);
+        let max_x = points.iter().map(|c| c.x).fold(f64::NEG_INFINITY, f64::max);
+        let min_y = points.iter().map(|c| c.y).fold(f64::INFINITY, f64::min);
+        let max_y = points.iter().map(|c| c.y).fold(f64::NEG_INFINITY, f64::max);
+        let dx = max_x - min_x;
+        let dy = max_y - min_y;
+        let d = if dx < dy {
+            dx
+        } else {
+            dy
+        };
+        d / 10.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::frame::Frame;
+    use crate::point::Point;
+    use crate::vector2::Vector2;
+
+    fn assert_near(expected: (f64, f64), actual: Point) {
+        assert_approx_eq!(expected.0, actual.x);
+        assert_approx_eq!(expected.1, actual.y);
+    }
+
+    #[test]
+    fn flip_x() {
+        let c = Point::new(10.0, 6.0);
+        let d = Frame::new(0.0, 0.0).flip_x();
+        let e = c.to_world(&d);
+        assert_eq!(Point::new(-10.0, 6.0), e);
+    }
+
+    #[test]
+    fn flip_y() {
+        let c = Point::new(10.0, 6.0);
+        let d = Frame::new(0.0, 0.0).flip_y();
+        let e = c.to_world(&d);
+        assert_eq!(Point::new(10.0, -6.0), e);
+    }
+
+    #[test]
+    fn round_trip() {
+        let worlds = [
+            Frame::new(10.0, 5.0).rotate(90.0),
+            Frame::new(-3.0, 2.0).flip_x().rotate(30.0).scale_x(2.0).scale_y(2.0),
+            Frame::new(1.0, 1.0).flip_y().scale_x(0.5),
+        ];
+        for world in &worlds {
+            for &(x, y) in &[(0.0, 0.0), (10.0, 6.0), (-2.5, 7.0)] {
+                let back = Point::new(x, y).to_world(world).from_world(world);
+                assert_near((x, y), back);
+            }
+        }
+        // the origin of the world is at its location
+        assert_near((0.0, 0.0), worlds[1].origin.to_world(&worlds[1]));
+        // rotated a quarter turn: the world x axis points along the reference y axis
+        assert_near((10.0, 7.0), Point::new(2.0, 0.0).from_world(&worlds[0]));
+        assert_near((2.0, 0.0), Point::new(10.0, 7.0).to_world(&worlds[0]));
+    }
+
+    #[test]
+    fn nested() {
+        let outer = Frame::new(10.0, 0.0).rotate(90.0);
+        let inner = Frame::new(2.0, 0.0).flip_y();
+        let m = Frame::chain(&[outer, inner]);
+        let p = Point::new(1.0, 1.0);
+        // (1, 1) in the inner world is (3, -1) in the outer one and (11, 3) in the reference
+        assert_near((11.0, 3.0), p.transform(&m));
+        assert_near((11.0, 3.0), p.from_world(&inner).from_world(&outer));
+        assert_near((1.0, 1.0), Point::new(11.0, 3.0).transform(&m.inverse()));
+        assert_near((1.0, 1.0), Point::new(11.0, 3.0).to_world(&outer).to_world(&inner));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let (a, b) = (Point::new(1.0, 2.0), Point::new(4.0, 6.0));
+        assert_eq!(Vector2::new(3.0, 4.0), b - a);
+        assert_eq!(5.0, a.distance(b));
+        assert_eq!(b, a + (b - a));
+        assert_eq!(a, b - Vector2::new(3.0, 4.0));
+        let mut c = a;
+        c += Vector2::new(1.0, 1.0);
+        c -= Vector2::new(0.0, 2.0);
+        assert_eq!(Point::new(2.0, 1.0), c);
+        assert_eq!(Vector2::new(1.0, 2.0), Vector2::from(a));
+        assert_eq!(a, Point::from(Vector2::new(1.0, 2.0)));
+    }
+}
@@ -0,0 +1,209 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! Measurements of polygons and polylines.
+//!
+//! A ring is a closed polygon given by its corners, without repeating the
+//! first corner at the end. Results are in the units of the coordinates.
+
+use std::f64::consts::PI;
+
+use crate::Point;
+use crate::vector2::Vector2;
+
+/// The direction in which the corners of a ring go around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    Counterclockwise,
+    Clockwise,
+    /// The ring has no surface.
+    Degenerate,
+}
+
+/// All edges of `ring`, including the one from the last corner back to the first.
+fn edges(ring: &[Point]) -> impl Iterator<Item=(Vector2, Vector2)> + '_ {
+    (0..ring.len()).map(move |i| (Vector2::from(&ring[i]), Vector2::from(&ring[(i + 1) % ring.len()])))
+}
+
+/// Surface of `ring`, positive when it is counterclockwise.
+pub fn signed_area(ring: &[Point]) -> f64 {
+    edges(ring).map(|(a, b)| a.perp_dot_product(b)).sum::<f64>() / 2.0
+}
+
+pub fn winding(ring: &[Point]) -> Winding {
+    let area = signed_area(ring);
+    if area > 0.0 {
+        Winding::Counterclockwise
+    } else if area < 0.0 {
+        Winding::Clockwise
+    } else {
+        Winding::Degenerate
+    }
+}
+
+/// Length of the outline of `ring`.
+pub fn perimeter(ring: &[Point]) -> f64 {
+    if ring.len() < 2 {
+        return 0.0;
+    }
+    edges(ring).map(|(a, b)| (b - a).length()).sum()
+}
+
+/// Length of the open polyline through `points`.
+pub fn length(points: &[Point]) -> f64 {
+    points.windows(2).map(|w| (Vector2::from(&w[1]) - Vector2::from(&w[0])).length()).sum()
+}
+
+/// Center of mass of the surface of `ring`, `None` when it has no surface.
+pub fn centroid(ring: &[Point]) -> Option<Point> {
+    let area = signed_area(ring);
+    if area == 0.0 || !area.is_finite() {
+        return None;
+    }
+    let sum = edges(ring).fold(Vector2::new(0.0, 0.0), |sum, (a, b)| sum + (a + b) * a.perp_dot_product(b));
+    let c = sum / (6.0 * area);
+    Some(Point::new(c.x, c.y))
+}
+
+/// Whether `point` is inside `ring`; points on the edges may go either way.
+pub fn contains(ring: &[Point], point: Point) -> bool {
+    let mut inside = false;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Whether `point` is inside the outline `rings[0]` and outside all holes
+/// `rings[1..]`, or more generally inside an odd number of the rings.
+pub fn contains_rings(rings: &[Vec<Point>], point: Point) -> bool {
+    rings.iter().filter(|r| contains(r, point)).count() % 2 == 1
+}
+
+/// Distance from `point` to the segment from `a` to `b`.
+pub fn segment_distance(point: Point, a: Point, b: Point) -> f64 {
+    let (p, a, b) = (Vector2::from(&point), Vector2::from(&a), Vector2::from(&b));
+    let ab = b - a;
+    let t = if ab.length() > 0.0 { ((p - a).dot_product(ab) / ab.dot_product(ab)).clamp(0.0, 1.0) } else { 0.0 };
+    (p - (a + ab * t)).length()
+}
+
+/// Distance from `point` to the open polyline through `points`, infinite
+/// when there are no points.
+pub fn polyline_distance(points: &[Point], point: Point) -> f64 {
+    match points {
+        [] => f64::INFINITY,
+        [a] => segment_distance(point, *a, *a),
+        _ => points.windows(2).map(|w| segment_distance(point, w[0], w[1])).fold(f64::INFINITY, f64::min),
+    }
+}
+
+/// Whether `ring` is a convex polygon: it turns the same way at every
+/// corner, and goes around exactly once. Straight corners are allowed.
+pub fn is_convex(ring: &[Point]) -> bool {
+    if ring.len() < 3 || signed_area(ring) == 0.0 {
+        return false;
+    }
+    let n = ring.len();
+    let mut sign = 0.0;
+    let mut turning = 0.0;
+    for i in 0..n {
+        let a = Vector2::from(&ring[(i + n - 1) % n]);
+        let b = Vector2::from(&ring[i]);
+        let c = Vector2::from(&ring[(i + 1) % n]);
+        let (e1, e2) = (b - a, c - b);
+        let cross = e1.perp_dot_product(e2);
+        if cross != 0.0 {
+            if sign * cross < 0.0 {
+                return false;
+            }
+            sign = cross;
+        }
+        turning += cross.atan2(e1.dot_product(e2));
+    }
+    (turning.abs() - 2.0 * PI).abs() < 1e-6
+}
+
+#[cfg(test)]
+mod test {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::{Area, Point};
+    use crate::graphic::Path;
+    use crate::curve::Curve;
+    use crate::polygon::{self, Winding};
+
+    fn ring(points: &[(f64, f64)]) -> Vec<Point> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    #[test]
+    fn square() {
+        let square = ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+        assert_eq!(16.0, polygon::signed_area(&square));
+        assert_eq!(16.0, polygon::perimeter(&square));
+        assert_eq!(Some(Point::new(2.0, 2.0)), polygon::centroid(&square));
+        assert_eq!(Winding::Counterclockwise, polygon::winding(&square));
+        assert!(polygon::is_convex(&square));
+        let reversed: Vec<Point> = square.iter().rev().cloned().collect();
+        assert_eq!(-16.0, polygon::signed_area(&reversed));
+        assert_eq!(Winding::Clockwise, polygon::winding(&reversed));
+        assert!(polygon::is_convex(&reversed));
+        assert!(polygon::contains(&square, Point::new(1.0, 3.0)));
+        assert!(!polygon::contains(&square, Point::new(5.0, 3.0)));
+        let hole = ring(&[(1.0, 1.0), (1.0, 2.0), (2.0, 2.0), (2.0, 1.0)]);
+        let rings = vec![square, hole];
+        assert!(!polygon::contains_rings(&rings, Point::new(1.5, 1.5)));
+        assert!(polygon::contains_rings(&rings, Point::new(3.5, 1.5)));
+        assert_eq!(1.0, polygon::polyline_distance(&rings[0], Point::new(2.0, -1.0)));
+        assert_eq!(5.0, polygon::segment_distance(Point::new(7.0, 4.0), Point::new(0.0, 0.0),
+                                                  Point::new(4.0, 0.0)));
+    }
+
+    #[test]
+    fn concave() {
+        let l = ring(&[(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (0.0, 2.0)]);
+        assert_eq!(3.0, polygon::signed_area(&l));
+        assert!(!polygon::is_convex(&l));
+        let c = polygon::centroid(&l).unwrap();
+        assert_approx_eq!(c.x, 5.0 / 6.0);
+        assert_approx_eq!(c.y, 5.0 / 6.0);
+        let star = ring(&[(0.0, 3.0), (2.0, -3.0), (-3.0, 1.0), (3.0, 1.0), (-2.0, -3.0)]);
+        assert!(!polygon::is_convex(&star));
+    }
+
+    #[test]
+    fn degenerate() {
+        let line = ring(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+        assert_eq!(Winding::Degenerate, polygon::winding(&line));
+        assert_eq!(None, polygon::centroid(&line));
+        assert!(!polygon::is_convex(&line));
+        assert_eq!(3.0, polygon::length(&ring(&[(0.0, 0.0), (3.0, 0.0)])) + polygon::length(&[]));
+    }
+
+    #[test]
+    fn area() {
+        let lawn = Area::new("lawn", vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)])
+            .hole(vec![(6.0, 6.0), (6.0, 8.0), (8.0, 8.0), (8.0, 6.0)]);
+        assert_eq!(96.0, lawn.surface());
+        assert_eq!(100.0, lawn.signed_area());
+        assert_eq!(48.0, lawn.perimeter());
+        let c = lawn.centroid().unwrap();
+        assert_approx_eq!(c.x, (100.0 * 5.0 - 4.0 * 7.0) / 96.0);
+        assert_approx_eq!(c.y, c.x);
+        assert_eq!(Winding::Counterclockwise, lawn.winding());
+        assert!(!lawn.is_convex());
+
+        // a half disc of radius 5
+        let bed = Area::new("bed", vec![(5.0, 0.0), (-5.0, 0.0)])
+            .curve(1, Curve::Arc { rx: 5.0, ry: 5.0, rotation: 0.0, large_arc: false, sweep: true });
+        assert_approx_eq!(bed.surface(), 12.5 * std::f64::consts::PI, 0.2);
+        assert_approx_eq!(bed.centroid().unwrap().y, 20.0 / (3.0 * std::f64::consts::PI), 0.01);
+        assert!(bed.is_convex());
+
+        let edge = Path::new("edge", vec![(0.0, 0.0), (3.0, 4.0), (3.0, 0.0)]);
+        assert_eq!(9.0, edge.length());
+    }
+}
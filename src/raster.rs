@@ -0,0 +1,349 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! CPU rasterization of a `Document` to PNG.
+//!
+//! Labels are drawn upright with a built-in bitmap font.
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::{Color, Document};
+use crate::backend::{self, Backend};
+use crate::font::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::graphic::World;
+use crate::matrix3::Matrix3;
+use crate::vector2::Vector2;
+
+/// Sub-scanlines per pixel row used for anti-aliasing.
+const SUBSAMPLES: usize = 4;
+
+type Ring = Vec<(f64, f64)>;
+
+/// An RGBA image.
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    /// Row major RGBA, not premultiplied.
+    pub pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// A fully transparent canvas.
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas { width, height, pixels: vec![0; width * height * 4] }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * self.width + x) * 4;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    fn blend(&mut self, x: usize, y: usize, color: (u8, u8, u8), alpha: f64) {
+        let i = (y * self.width + x) * 4;
+        let dst_alpha = self.pixels[i + 3] as f64 / 255.0;
+        let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+        if out_alpha <= 0.0 {
+            return;
+        }
+        let mix = |src: u8, dst: u8| {
+            ((src as f64 * alpha + dst as f64 * dst_alpha * (1.0 - alpha)) / out_alpha).round() as u8
+        };
+        self.pixels[i] = mix(color.0, self.pixels[i]);
+        self.pixels[i + 1] = mix(color.1, self.pixels[i + 1]);
+        self.pixels[i + 2] = mix(color.2, self.pixels[i + 2]);
+        self.pixels[i + 3] = (out_alpha * 255.0).round() as u8;
+    }
+
+    /// Fill the rings in pixel coordinates using the non-zero winding rule.
+    pub fn fill(&mut self, rings: &[Ring], color: (u8, u8, u8)) {
+        let mut edges = vec![];
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for ring in rings {
+            for (i, &(x0, y0)) in ring.iter().enumerate() {
+                let (x1, y1) = ring[(i + 1) % ring.len()];
+                min_x = min_x.min(x0);
+                max_x = max_x.max(x0);
+                min_y = min_y.min(y0);
+                max_y = max_y.max(y0);
+                if y0 != y1 {
+                    edges.push((x0, y0, x1, y1, if y1 > y0 { 1 } else { -1 }));
+                }
+            }
+        }
+        if edges.is_empty() || !(min_x.is_finite() && max_x.is_finite() && min_y.is_finite() && max_y.is_finite()) {
+            return;
+        }
+        let col_min = min_x.floor().max(0.0).min(self.width as f64) as usize;
+        let col_max = max_x.ceil().max(0.0).min(self.width as f64) as usize;
+        let row_min = min_y.floor().max(0.0).min(self.height as f64) as usize;
+        let row_max = max_y.ceil().max(0.0).min(self.height as f64) as usize;
+        if col_min >= col_max {
+            return;
+        }
+        let mut coverage = vec![0.0; col_max - col_min];
+        let mut crossings: Vec<(f64, i32)> = vec![];
+        for row in row_min..row_max {
+            coverage.iter_mut().for_each(|c| *c = 0.0);
+            for sub in 0..SUBSAMPLES {
+                let y = row as f64 + (sub as f64 + 0.5) / SUBSAMPLES as f64;
+                crossings.clear();
+                for &(x0, y0, x1, y1, dir) in &edges {
+                    if (y0 <= y && y < y1) || (y1 <= y && y < y0) {
+                        crossings.push((x0 + (y - y0) * (x1 - x0) / (y1 - y0), dir));
+                    }
+                }
+                crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+                let mut winding = 0;
+                let mut start = 0.0;
+                for &(x, dir) in &crossings {
+                    let before = winding;
+                    winding += dir;
+                    if before == 0 && winding != 0 {
+                        start = x;
+                    } else if before != 0 && winding == 0 {
+                        add_span(&mut coverage, col_min, start, x);
+                    }
+                }
+            }
+            for (i, &c) in coverage.iter().enumerate() {
+                if c > 0.0 {
+                    self.blend(col_min + i, row, color, c.min(1.0));
+                }
+            }
+        }
+    }
+
+    /// Stroke a polyline in pixel coordinates with round joins.
+    pub fn stroke(&mut self, points: &[(f64, f64)], closed: bool, width: f64, color: (u8, u8, u8)) {
+        let half = width / 2.0;
+        let mut rings = vec![];
+        let segments = if closed { points.len() } else { points.len().saturating_sub(1) };
+        for i in 0..segments {
+            let (x0, y0) = points[i];
+            let (x1, y1) = points[(i + 1) % points.len()];
+            let length = ((x1 - x0) * (x1 - x0) + (y1 - y0) * (y1 - y0)).sqrt();
+            if length == 0.0 {
+                continue;
+            }
+            let (nx, ny) = (-(y1 - y0) / length * half, (x1 - x0) / length * half);
+            rings.push(oriented(vec![(x0 + nx, y0 + ny), (x1 + nx, y1 + ny), (x1 - nx, y1 - ny), (x0 - nx, y0 - ny)]));
+        }
+        let joins = if closed { 0..points.len() } else { 1..points.len().saturating_sub(1) };
+        for i in joins {
+            rings.push(circle(points[i].0, points[i].1, half));
+        }
+        self.fill(&rings, color);
+    }
+
+    /// Draw `text` centered on `x` with its baseline at `y`, `size` in pixels.
+    pub fn text(&mut self, x: f64, y: f64, size: f64, text: &str, color: (u8, u8, u8)) {
+        let dot = size / 10.0;
+        let advance = (GLYPH_WIDTH + 1) as f64 * dot;
+        let count = text.chars().count() as f64;
+        let left = x - (count * advance - dot) / 2.0;
+        let top = y - GLYPH_HEIGHT as f64 * dot;
+        let mut rings = vec![];
+        for (n, c) in text.chars().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                for row in 0..GLYPH_HEIGHT {
+                    if font::dot(c, column, row) {
+                        let x0 = left + n as f64 * advance + column as f64 * dot;
+                        let y0 = top + row as f64 * dot;
+                        rings.push(oriented(vec![(x0, y0), (x0 + dot, y0), (x0 + dot, y0 + dot), (x0, y0 + dot)]));
+                    }
+                }
+            }
+        }
+        self.fill(&rings, color);
+    }
+
+    /// Encode the canvas as an RGBA PNG.
+    pub fn write_png<W: Write>(&self, out: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(&self.pixels).map_err(io::Error::other)
+    }
+}
+
+fn add_span(coverage: &mut [f64], offset: usize, start: f64, end: f64) {
+    let start = start.max(offset as f64);
+    let end = end.min((offset + coverage.len()) as f64);
+    if start >= end {
+        return;
+    }
+    for px in (start.floor() as usize)..(end.ceil() as usize) {
+        let overlap = end.min(px as f64 + 1.0) - start.max(px as f64);
+        coverage[px - offset] += overlap / SUBSAMPLES as f64;
+    }
+}
+
+fn signed_area(ring: &[(f64, f64)]) -> f64 {
+    (0..ring.len()).map(|i| {
+        let (x0, y0) = ring[i];
+        let (x1, y1) = ring[(i + 1) % ring.len()];
+        x0 * y1 - x1 * y0
+    }).sum::<f64>() / 2.0
+}
+
+/// The ring with positive orientation, so overlapping rings add up under the non-zero rule.
+fn oriented(mut ring: Ring) -> Ring {
+    if signed_area(&ring) < 0.0 {
+        ring.reverse();
+    }
+    ring
+}
+
+fn circle(cx: f64, cy: f64, r: f64) -> Ring {
+    let n = ((r * 2.0).ceil() as usize).clamp(16, 360);
+    (0..n).map(|i| {
+        let a = i as f64 * 2.0 * std::f64::consts::PI / n as f64;
+        (cx + r * a.cos(), cy + r * a.sin())
+    }).collect()
+}
+
+/// Split a polyline in the dashes of `pattern`.
+fn dashes(points: &[(f64, f64)], pattern: &[f64]) -> Vec<Ring> {
+    if pattern.iter().sum::<f64>() <= 0.0 {
+        return vec![points.to_vec()];
+    }
+    let mut result = vec![];
+    let mut current = vec![];
+    let mut index = 0;
+    let mut left = pattern[0];
+    for w in points.windows(2) {
+        let (mut x0, mut y0) = w[0];
+        let (x1, y1) = w[1];
+        let mut length = ((x1 - x0) * (x1 - x0) + (y1 - y0) * (y1 - y0)).sqrt();
+        if index % 2 == 0 && current.is_empty() {
+            current.push((x0, y0));
+        }
+        while length > left {
+            let t = left / length;
+            x0 += (x1 - x0) * t;
+            y0 += (y1 - y0) * t;
+            length -= left;
+            if index % 2 == 0 {
+                current.push((x0, y0));
+                result.push(std::mem::take(&mut current));
+            } else {
+                current.push((x0, y0));
+            }
+            index = (index + 1) % pattern.len();
+            left = pattern[index];
+        }
+        left -= length;
+        if index % 2 == 0 {
+            current.push((x1, y1));
+        }
+    }
+    if current.len() > 1 {
+        result.push(current);
+    }
+    result
+}
+
+impl Backend for Canvas {
+    fn polygon(&mut self, rings: &[Vec<(f64, f64)>], stroke: Color, fill: Color, stroke_width: f64) {
+        if let Some(fill) = fill.rgb() {
+            self.fill(rings, fill);
+        }
+        if let Some(stroke) = stroke.rgb() {
+            rings.iter().for_each(|r| self.stroke(r, true, stroke_width, stroke));
+        }
+    }
+
+    fn polyline(&mut self, points: &[(f64, f64)], stroke: Color, stroke_width: f64, dash: &[f64]) {
+        if let Some(stroke) = stroke.rgb() {
+            for dash in dashes(points, dash) {
+                self.stroke(&dash, false, stroke_width, stroke);
+            }
+        }
+    }
+
+    fn circle(&mut self, (cx, cy): (f64, f64), r: f64, stroke: Color, fill: Color, stroke_width: f64) {
+        if let Some(fill) = fill.rgb() {
+            self.fill(&[circle(cx, cy, r)], fill);
+        }
+        if let Some(stroke) = stroke.rgb() {
+            let half = stroke_width / 2.0;
+            let mut inner = circle(cx, cy, (r - half).max(0.0));
+            inner.reverse();
+            self.fill(&[circle(cx, cy, r + half), inner], stroke);
+        }
+    }
+
+    fn text(&mut self, (x, y): (f64, f64), size: f64, text: &str) {
+        Canvas::text(self, x, y, size, text, Color::Black.rgb().unwrap());
+    }
+}
+
+/// Rasterize the document at `pixels_per_unit` resolution.
+pub fn render(document: &Document) -> Canvas {
+    let view = document.view_box();
+    let pixels_per_unit = document.pixels_per_unit as f64;
+    let width = (view.width() * pixels_per_unit).ceil().max(0.0) as usize;
+    let height = (view.height() * pixels_per_unit).ceil().max(0.0) as usize;
+    let matrix = World::new("Document", document.origin).matrix()
+        * Matrix3::translate(Vector2::new(-view.min_x, -view.min_y))
+        * Matrix3::scale(pixels_per_unit, pixels_per_unit);
+    let mut canvas = Canvas::new(width, height);
+    backend::draw(document, &matrix, &mut canvas);
+    canvas
+}
+
+impl Document {
+    /// Write the document as PNG to `filename`.
+    pub fn save_png(&self, filename: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(filename)?);
+        render(self).write_png(&mut out)?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Area, Color, Document};
+    use crate::raster::{self, dashes};
+
+    #[test]
+    fn fill_area() {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 2);
+        doc.add(Area::new("square", vec![(2.0, 2.0), (8.0, 2.0), (8.0, 8.0), (2.0, 8.0)])
+            .color(Color::None).fill(Color::Red));
+        let canvas = raster::render(&doc);
+        assert_eq!(20, canvas.width);
+        assert_eq!(20, canvas.height);
+        assert_eq!([255, 0, 0, 255], canvas.pixel(10, 10));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(18, 1));
+    }
+
+    #[test]
+    fn fill_hole() {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 2);
+        // the hole has the same winding as the outline
+        doc.add(Area::new("square", vec![(2.0, 2.0), (8.0, 2.0), (8.0, 8.0), (2.0, 8.0)])
+            .hole(vec![(4.0, 4.0), (6.0, 4.0), (6.0, 6.0), (4.0, 6.0)])
+            .color(Color::None).fill(Color::Red));
+        let canvas = raster::render(&doc);
+        assert_eq!([0, 0, 0, 0], canvas.pixel(10, 10));
+        assert_eq!([255, 0, 0, 255], canvas.pixel(6, 10));
+    }
+
+    #[test]
+    fn png() {
+        let doc = Document::new(0.0, 0.0, 4.0, 3.0, 10);
+        let mut out = vec![];
+        raster::render(&doc).write_png(&mut out).unwrap();
+        assert_eq!(&[0x89, b'P', b'N', b'G'], &out[0..4]);
+    }
+
+    #[test]
+    fn dash() {
+        let d = dashes(&[(0.0, 0.0), (10.0, 0.0)], &[3.0, 1.0]);
+        assert_eq!(vec![vec![(0.0, 0.0), (3.0, 0.0)], vec![(4.0, 0.0), (7.0, 0.0)], vec![(8.0, 0.0), (10.0, 0.0)]], d);
+    }
+}
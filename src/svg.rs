@@ -1,64 +1,663 @@
 // (c) 2019 Joost Yervante Damad <joost@damad.be>
 
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, BufWriter, Write};
 
 use simple_xml_serialize::XMLElement;
 
-use crate::Coordinate;
+use crate::Point;
+use crate::frame::Frame;
+use crate::bounding_box::BoundingBox;
+use crate::drawable::Drawable;
+use crate::element::{self, Element, Walk};
+use crate::error::{self, Result};
 use crate::graphic::World;
+use crate::matrix3::Matrix3;
+use crate::path_data::PathFormat;
+use crate::util;
 
+/// Default stroke width of a document.
+pub const STROKE_WIDTH: f64 = 0.25;
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n";
+
+/// How a document is written as SVG.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// Indentation of nested elements; `None` writes everything on one line.
+    pub indent: Option<String>,
+    /// Decimal places of numbers in attributes; `None` writes them in full.
+    pub precision: Option<usize>,
+    /// Start the output with an `<?xml ?>` declaration.
+    pub xml_declaration: bool,
+}
+
+impl SvgOptions {
+    pub fn new() -> SvgOptions {
+        SvgOptions { indent: None, precision: None, xml_declaration: false }
+    }
+
+    /// Options indenting nested elements by two spaces.
+    pub fn pretty() -> SvgOptions {
+        SvgOptions::new().indent("  ")
+    }
+
+    pub fn indent<T: ToString>(self, indent: T) -> Self {
+        SvgOptions { indent: Some(indent.to_string()), ..self }
+    }
+
+    pub fn precision(self, precision: usize) -> Self {
+        SvgOptions { precision: Some(precision), ..self }
+    }
+
+    pub fn xml_declaration(self) -> Self {
+        SvgOptions { xml_declaration: true, ..self }
+    }
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions::new()
+    }
+}
+
+/// Attributes holding coordinates or lengths, which are rounded to the
+/// precision. Transforms are written in full: rounding their coefficients
+/// would distort rotations by an error growing with the distance to the origin.
+const ROUNDED_ATTRIBUTES: &[&str] = &[
+    "x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "r", "width", "height", "points", "d", "viewBox",
+    "stroke-width", "font-size",
+];
+
+/// Round the numbers in the coordinate and length attributes of `xml`.
+///
+/// Path data of paths and areas is already written at the precision when
+/// rendering; this rounds the attributes of the other elements, including
+/// path data of custom drawables.
+fn round_attributes(xml: &mut XMLElement, precision: usize) {
+    for attr in xml.attrs.iter_mut().flatten() {
+        if ROUNDED_ATTRIBUTES.contains(&attr.name.as_str()) {
+            attr.value = util::round_numbers(&attr.value, precision);
+        }
+    }
+    for child in xml.contents.iter_mut().flatten() {
+        round_attributes(child, precision);
+    }
+}
+
+/// Replace the characters XML does not allow literally in the attribute
+/// values and text of `xml` and its contents by entities.
+fn escape(xml: &mut XMLElement) {
+    for attr in xml.attrs.iter_mut().flatten() {
+        attr.value = util::escape_xml(&attr.value);
+    }
+    if let Some(text) = &mut xml.text {
+        *text = util::escape_xml(text);
+    }
+    for child in xml.contents.iter_mut().flatten() {
+        escape(child);
+    }
+}
+
+/// Stands for the contents of an element when splitting its tags; a NUL
+/// character can not occur in XML.
+const CONTENTS: &str = "\u{0}";
+
+/// The opening and closing tags of `xml`, which has no contents, as
+/// `simple_xml_serialize` writes them, so streamed output matches
+/// `XMLElement::to_string`. Text of `xml` is part of the opening tag.
+fn tags(mut xml: XMLElement) -> (String, String) {
+    let text = xml.text.take().unwrap_or_default();
+    let xml = XMLElement { text: Some(text + CONTENTS), ..xml };
+    let s = xml.to_string();
+    let split = s.find(CONTENTS).expect("text is written");
+    (s[..split].to_string(), s[split + CONTENTS.len()..].to_string())
+}
+
+/// Writes a document as SVG element by element.
+///
+/// Elements are rendered and written one at a time, and worlds are written
+/// child by child, so the memory used does not grow with the size of the plan.
+///
+/// ```no_run
+/// # use jyd2d::{Point, Document, Label};
+/// # use jyd2d::svg::{SvgOptions, SvgWriter};
+/// let file = std::fs::File::create("orchard.svg")?;
+/// let header = Document::new(0.0, 0.0, 100.0, 100.0, 10);
+/// let mut writer = SvgWriter::new(file, &header, &SvgOptions::new())?;
+/// for i in 0..10000 {
+///     writer.add(Label::new(Point::new((i % 100) as f64, (i / 100) as f64), "x"))?;
+/// }
+/// writer.finish()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct SvgWriter<W: Write> {
+    out: W,
+    options: SvgOptions,
+    precision: Option<usize>,
+    path_format: PathFormat,
+    /// Closing tags of the elements written so far but not yet closed.
+    closing: Vec<String>,
+}
+
+impl<W: Write> SvgWriter<W> {
+    /// Start writing `document`, followed by the children it already has.
+    ///
+    /// The view box, precision and path format are those of `document`; a
+    /// document fitted to its content is only fitted to the children it has
+    /// when the writer is created. A precision in `options` overrides the
+    /// precision of the document.
+    pub fn new(out: W, document: &Document, options: &SvgOptions) -> io::Result<SvgWriter<W>> {
+        let mut writer = SvgWriter {
+            out,
+            options: options.clone(),
+            precision: options.precision.or(document.precision),
+            path_format: document.path_format,
+            closing: vec![],
+        };
+        if options.xml_declaration {
+            writer.out.write_all(XML_DECLARATION.as_bytes())?;
+        }
+        writer.open(document.svg_element())?;
+        writer.open_world(&World::new("Document", document.origin))?;
+        for child in &document.children {
+            writer.write_element(child)?;
+        }
+        Ok(writer)
+    }
+
+    /// Write one more top level element.
+    pub fn add<X: Into<Element>>(&mut self, x: X) -> io::Result<()> {
+        self.write_element(&x.into())
+    }
+
+    /// Write `element`, and for a world all its children, without copying it.
+    pub fn write_element(&mut self, element: &Element) -> io::Result<()> {
+        match element {
+            Element::World(world) => {
+                self.open_world(world)?;
+                for e in &world.elements {
+                    self.write_element(e)?;
+                }
+                self.close()
+            }
+            e => self.write_xml(e.clone().into_svg(self.path_format, self.precision)),
+        }
+    }
+
+    /// Close the document and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.close()?;
+        self.close()?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn newline(&self) -> &'static str {
+        if self.options.indent.is_some() { "\n" } else { "" }
+    }
+
+    fn pad(&self) -> String {
+        self.options.indent.as_deref().unwrap_or("").repeat(self.closing.len())
+    }
+
+    fn format(&self, xml: &mut XMLElement) {
+        if let Some(precision) = self.precision {
+            round_attributes(xml, precision);
+        }
+        escape(xml);
+    }
+
+    /// Write the opening tag of `xml` followed by the contents it has.
+    fn open(&mut self, mut xml: XMLElement) -> io::Result<()> {
+        let contents = xml.contents.take();
+        self.format(&mut xml);
+        let (opening, closing) = tags(xml);
+        let tag = format!("{}{}{}", self.pad(), opening, self.newline());
+        self.out.write_all(tag.as_bytes())?;
+        self.closing.push(closing);
+        contents.into_iter().flatten().try_for_each(|c| self.write_xml(c))
+    }
+
+    /// The world element with its axis and title, but without its children.
+    fn open_world(&mut self, world: &World) -> io::Result<()> {
+        let shell = World {
+            location: world.location,
+            elements: vec![],
+            axis_scale: world.axis_scale,
+            show_axis: world.show_axis,
+            name: world.name.clone(),
+            stroke_width: world.stroke_width,
+        };
+        self.open(shell.into())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        let closing = self.closing.pop().expect("an open element");
+        let tag = format!("{}{}{}", self.pad(), closing, self.newline());
+        self.out.write_all(tag.as_bytes())
+    }
+
+    fn write_xml(&mut self, mut xml: XMLElement) -> io::Result<()> {
+        self.format(&mut xml);
+        match &self.options.indent {
+            Some(indent) => {
+                let pad = self.pad();
+                for line in xml.to_string_pretty("\n", indent).lines() {
+                    writeln!(self.out, "{}{}", pad, line)?;
+                }
+                Ok(())
+            }
+            None => self.out.write_all(xml.to_string().as_bytes()),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct Document {
     pub min_x: f64,
     pub min_y: f64,
     pub width: f64,
     pub height: f64,
     pub pixels_per_unit: i64,
-    pub origin: Coordinate,
-    children: Vec<XMLElement>,
+    pub origin: Frame,
+    /// When set the view box is fitted to the content with this margin.
+    pub fit_margin: Option<f64>,
+    /// Decimal places of numbers in SVG output; `None` writes them in full.
+    pub precision: Option<usize>,
+    /// How path data is written in SVG output.
+    pub path_format: PathFormat,
+    children: Vec<Element>,
 }
 
 impl Document {
     pub fn new(min_x: f64, min_y: f64, width: f64, height: f64, pixels_per_unit: i64) -> Document {
-        let origin = Coordinate::new(min_x, min_y + height).flip_y();
-        Document { min_x, min_y, width, height, pixels_per_unit, origin, children: vec![] }
+        let origin = Frame::new(min_x, min_y + height).flip_y();
+        Document {
+            min_x,
+            min_y,
+            width,
+            height,
+            pixels_per_unit,
+            origin,
+            fit_margin: None,
+            precision: None,
+            path_format: PathFormat::new(),
+            children: vec![],
+        }
     }
 
-    pub fn save(&self, filename: &str) -> std::io::Result<()> {
-        let mut out = File::create(filename)?;
-        let xml: XMLElement = self.into();
-        out.write(xml.to_string().as_bytes())?;
-        Ok(())
+    /// A document whose view box is computed from its content when rendering,
+    /// with `margin` plan units of space around it.
+    pub fn auto_fit(pixels_per_unit: i64, margin: f64) -> Document {
+        let origin = Frame::new(0.0, 0.0).flip_y();
+        Document {
+            min_x: 0.0,
+            min_y: 0.0,
+            width: 0.0,
+            height: 0.0,
+            pixels_per_unit,
+            origin,
+            fit_margin: Some(margin),
+            precision: None,
+            path_format: PathFormat::new(),
+            children: vec![],
+        }
+    }
+
+    /// Union of the bounding boxes of all elements, in plan coordinates.
+    pub fn content_bounding_box(&self) -> Option<BoundingBox> {
+        self.children.iter()
+            .filter_map(|c| c.bounding_box())
+            .fold(None, |acc, b| Some(BoundingBox::union_opt(acc, b)))
+    }
+
+    /// The view box in SVG coordinates.
+    pub fn view_box(&self) -> BoundingBox {
+        match self.fit_margin {
+            None => BoundingBox::new(self.min_x, self.min_y, self.min_x + self.width, self.min_y + self.height),
+            Some(margin) => {
+                let matrix = World::new("Document", self.origin).matrix();
+                self.content_bounding_box()
+                    .unwrap_or_else(|| BoundingBox::point(0.0, 0.0))
+                    .transform(&matrix)
+                    .grow(margin)
+            }
+        }
+    }
+
+    /// The document as SVG text.
+    pub fn to_svg_string_with(&self, options: &SvgOptions) -> String {
+        let mut out = vec![];
+        self.write_to(&mut out, options).expect("writing to memory can not fail");
+        String::from_utf8(out).expect("SVG output is UTF-8")
+    }
+
+    pub fn to_svg_string(&self) -> String {
+        self.to_svg_string_with(&SvgOptions::new())
     }
 
-    pub fn pretty_save(&self, filename: &str) -> std::io::Result<()> {
-        let mut out = File::create(filename)?;
-        let xml: XMLElement = self.into();
-        out.write(xml.to_string_pretty("\n", "  ").as_bytes())?;
+    pub fn to_pretty_svg_string(&self) -> String {
+        self.to_svg_string_with(&SvgOptions::pretty())
+    }
+
+    /// Write the document as SVG to `out`, element by element.
+    pub fn write_to<W: Write>(&self, out: W, options: &SvgOptions) -> io::Result<()> {
+        SvgWriter::new(out, self, options)?.finish()?;
         Ok(())
     }
 
-    pub fn add<X: Into<XMLElement>>(&mut self, x: X) {
+    /// Write the document as SVG to `filename`.
+    pub fn save_with(&self, filename: &str, options: &SvgOptions) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(filename)?);
+        self.write_to(&mut out, options)?;
+        out.flush()
+    }
+
+    pub fn save(&self, filename: &str) -> io::Result<()> {
+        self.save_with(filename, &SvgOptions::new())
+    }
+
+    pub fn pretty_save(&self, filename: &str) -> io::Result<()> {
+        self.save_with(filename, &SvgOptions::pretty())
+    }
+
+    /// Check the extents of the document and the geometry of all its elements.
+    pub fn validate(&self) -> Result<()> {
+        error::check_finite("document", "min x", self.min_x)?;
+        error::check_finite("document", "min y", self.min_y)?;
+        error::check_size("document", "width", self.width)?;
+        error::check_size("document", "height", self.height)?;
+        if let Some(margin) = self.fit_margin {
+            error::check_finite("document", "margin", margin)?;
+        }
+        self.children.iter().try_for_each(|e| e.validate())
+    }
+
+    pub fn add<X: Into<Element>>(&mut self, x: X) {
         self.children.push(x.into())
     }
+
+    /// The top level elements of the document.
+    pub fn children(&self) -> &[Element] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut [Element] {
+        &mut self.children
+    }
+
+    /// Keep only the top level elements for which `keep` returns true.
+    pub fn retain<F: FnMut(&Element) -> bool>(&mut self, keep: F) {
+        self.children.retain(keep)
+    }
+
+    /// Depth-first iterator over all elements in the document.
+    pub fn walk(&self) -> Walk<'_> {
+        Walk::new(&self.children)
+    }
+
+    /// Find the first element with the given name.
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.walk().find(|e| e.name() == Some(name))
+    }
+
+    /// Find the first element with the given name for modification.
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Element> {
+        element::find_mut(&mut self.children, name)
+    }
+
+    /// Find the first element with the given SVG id.
+    pub fn find_id(&self, id: &str) -> Option<&Element> {
+        self.walk().find(|e| e.id().as_deref() == Some(id))
+    }
+
+    /// The names of all elements at `point` in plan coordinates, depth-first,
+    /// see `Element::hit`.
+    pub fn hit_test(&self, point: Point) -> Vec<String> {
+        let mut hits = vec![];
+        element::hit_test(&self.children, point, &Matrix3::unit(), STROKE_WIDTH, &mut hits);
+        hits
+    }
+
+    /// The matrix mapping coordinates inside the first world, or area with a
+    /// world, with the given name to plan coordinates, through all worlds it is in.
+    pub fn frame(&self, name: &str) -> Option<Matrix3> {
+        element::frame(&self.children, name, &Matrix3::unit())
+    }
+
+    /// The frames of the worlds the first element with the given name is in,
+    /// outermost first, see `Element::frames`.
+    pub fn frames(&self, name: &str) -> Option<Vec<Frame>> {
+        let mut stack = vec![];
+        if element::frames(&self.children, name, &mut stack) { Some(stack) } else { None }
+    }
+
+    /// The plan coordinates of `point`, given in the coordinates of the
+    /// first element with the given name, through all worlds it is in.
+    pub fn absolute(&self, name: &str, point: Point) -> Option<Point> {
+        self.frames(name).map(|frames| point.transform(&Frame::chain(&frames)))
+    }
+
+    /// Find the first element with the given name, placed in plan
+    /// coordinates by the worlds it is in, see `Element::find_placed`.
+    pub fn find_placed(&self, name: &str) -> Option<Element> {
+        element::find_placed(&self.children, name, &Matrix3::unit())
+    }
 }
 
-impl Into<XMLElement> for &Document {
-    fn into(self) -> XMLElement {
-        let view_box = format!("{} {} {} {}", self.min_x, self.min_y, self.width, self.height);
+impl Document {
+    /// The `svg` element, without content.
+    fn svg_element(&self) -> XMLElement {
+        let view = self.view_box();
+        let view_box = format!("{} {} {} {}", view.min_x, view.min_y, view.width(), view.height());
         let pixels_per_unit = self.pixels_per_unit as f64;
-        let pixel_width = (self.width * pixels_per_unit).ceil() as i64;
-        let pixel_height = (self.height * pixels_per_unit).ceil() as i64;
-        let mut world = World::new("Document", self.origin);
-        for child in &self.children {
-            world.add(child)
-        }
+        let pixel_width = (view.width() * pixels_per_unit).ceil() as i64;
+        let pixel_height = (view.height() * pixels_per_unit).ceil() as i64;
         XMLElement::new("svg")
             .attr("width", pixel_width)
             .attr("height", pixel_height)
             .attr("viewBox", view_box)
             .attr("xmlns", "http://www.w3.org/2000/svg")
-            .attr("stroke-width", 0.25)
-            .element(world)
+            .attr("stroke-width", STROKE_WIDTH)
+    }
+}
+
+/// The whole document as one XML tree, the same as what `write_to` writes
+/// with the default options.
+impl Into<XMLElement> for &Document {
+    fn into(self) -> XMLElement {
+        let mut world = World::new("Document", self.origin);
+        world.elements = self.children.clone();
+        let mut xml = self.svg_element().element(world.into_svg(self.path_format, self.precision));
+        if let Some(precision) = self.precision {
+            round_attributes(&mut xml, precision);
+        }
+        escape(&mut xml);
+        xml
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use assert_approx_eq::assert_approx_eq;
+    use simple_xml_serialize::XMLElement;
+
+    use crate::{Area, Color, Frame, Point, Document, Label};
+    use crate::graphic::Path;
+    use crate::graphic::World;
+    use crate::matrix3::Matrix3;
+    use crate::path_data::PathFormat;
+    use crate::svg::{SvgOptions, SvgWriter};
+    use crate::tree::Tree;
+
+    #[test]
+    fn auto_fit() {
+        let mut doc = Document::auto_fit(10, 1.0);
+        doc.add(Area::new("plot", vec![(0.0, 0.0), (20.0, 0.0), (20.0, 10.0), (0.0, 10.0)]).color(Color::Black));
+        doc.add(Tree {
+            name: "oak".into(),
+            species: "Quercus robur".into(),
+            trunk_diameter: 0.5,
+            crown_diameter: Some(8.0),
+            location: Point::new(22.0, 5.0),
+            label_location: Point::new(22.0, 5.0),
+        });
+        doc.add(Label::new(Point::new(10.0, -3.0), "x").size(1.0));
+        let view = doc.view_box();
+        assert_approx_eq!(view.min_x, -1.0);
+        // the tree label has the default font size of 16
+        assert_approx_eq!(view.max_x, 22.0 + 3.0 * 16.0 * 0.3 + 1.0);
+        // plan y is flipped: plan y range [-11, 10], down to the top of the
+        // tree label, becomes [-10, 11]
+        assert_approx_eq!(view.min_y, -11.0);
+        assert_approx_eq!(view.max_y, 12.0);
+    }
+
+    #[test]
+    fn xml_element() {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        doc.precision = Some(2);
+        doc.path_format = PathFormat::compact();
+        let mut bed = Area::new("bed & border", vec![(1.0, 1.0), (3.0, 1.0), (3.0, 1.0 / 3.0)])
+            .world(Point::new(1.0, 1.0), None);
+        bed.add(Path::new("edge", vec![(0.0, 0.0), (0.0, 2.0 / 3.0)]));
+        doc.add(bed);
+        doc.add(Label::new(Point::new(0.5, 0.25), "<rose>"));
+        let svg = doc.to_svg_string();
+        assert!(svg.contains("d=\"M0,0 v0.67\""));
+        let xml: XMLElement = (&doc).into();
+        assert_eq!(svg, xml.to_string());
+
+        let file = std::env::temp_dir().join(format!("jyd2d-svg-{}.svg", std::process::id()));
+        let filename = file.to_str().unwrap();
+        doc.save(filename).unwrap();
+        let saved = std::fs::read_to_string(filename);
+        std::fs::remove_file(filename).unwrap();
+        assert_eq!(svg, saved.unwrap());
+    }
+
+    #[test]
+    fn compact_paths() {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        doc.precision = Some(3);
+        doc.path_format = PathFormat::compact();
+        let mut bed = Area::new("bed", vec![(1.0, 1.0), (3.0000000000000004, 1.0), (3.0, 2.5), (1.0, 2.5)])
+            .world(Point::new(1.0, 1.0), None);
+        // paths in the world of an area are formatted too
+        bed.add(Path::new("edge", vec![(0.0, 0.0), (0.0, 1.0 / 3.0)]));
+        doc.add(bed);
+        let svg = doc.to_svg_string();
+        assert!(svg.contains("d=\"M1,1 h2 v1.5 h-2 z\""));
+        assert!(svg.contains("d=\"M0,0 v0.333\""));
+        assert!(doc.to_svg_string_with(&SvgOptions::new().precision(0)).contains("d=\"M1,1 h2 v2 h-2 z\""));
+    }
+
+    #[test]
+    fn write_to() {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        doc.add(Path::new("hedge", vec![(0.1 + 0.2, 1.0), (5.0, 1.0 / 3.0)]));
+        let mut out = vec![];
+        doc.write_to(&mut out, &SvgOptions::new()).unwrap();
+        assert_eq!(doc.to_svg_string(), String::from_utf8(out).unwrap());
+        assert!(doc.to_svg_string().contains("M0.30000000000000004,1 L"));
+
+        let svg = doc.to_svg_string_with(&SvgOptions::pretty().precision(2).xml_declaration());
+        assert!(svg.starts_with("<?xml version=\"1.0\""));
+        assert!(svg.contains("d=\"M0.3,1 L5,0.33\""));
+        assert!(svg.contains("id=\"area-hedge\""));
+        assert!(svg.contains("\n  <g"));
+    }
+
+    #[test]
+    fn precision_keeps_rotation() {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        let world = World::new("site", Frame::new(1.0, 2.0).rotate(15.0));
+        doc.add(world.clone());
+        for precision in 0..3 {
+            let svg = doc.to_svg_string_with(&SvgOptions::new().precision(precision));
+            let start = svg.find("id=\"world-site\" transform=\"").unwrap() + "id=\"world-site\" transform=\"".len();
+            let transform = &svg[start..start + svg[start..].find('"').unwrap()];
+            Matrix3::parse_svg(transform).unwrap().assert_approx_eq(world.matrix());
+        }
+    }
+
+    #[test]
+    fn streaming() {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        let mut world = World::new("orchard", Point::new(2.0, 3.0));
+        world.add(Label::new(Point::new(1.0, 1.0), "apple"));
+        world.add(World::new("row", Point::new(1.0, 0.0)));
+        doc.add(world);
+        doc.add(Path::new("hedge", vec![(0.0, 1.0), (5.0, 1.0)]));
+        let xml: XMLElement = (&doc).into();
+        assert_eq!(xml.to_string(), doc.to_svg_string());
+        assert_eq!(xml.to_string_pretty("\n", "  "), doc.to_pretty_svg_string());
+
+        let header = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        let mut writer = SvgWriter::new(vec![], &header, &SvgOptions::new()).unwrap();
+        for child in doc.children() {
+            writer.add(child.clone()).unwrap();
+        }
+        assert_eq!(doc.to_svg_string(), String::from_utf8(writer.finish().unwrap()).unwrap());
+    }
+
+    #[test]
+    fn escaping() {
+        let mut doc = Document::new(0.0, 0.0, 10.0, 10.0, 10);
+        let mut world = World::new("Tom & Jerry", Point::new(2.0, 3.0));
+        world.add(Label::new(Point::new(1.0, 1.0), "<\"cheese\">"));
+        doc.add(world);
+        let svg = doc.to_svg_string();
+        assert!(svg.contains("id=\"world-Tom &amp; Jerry\""));
+        assert!(svg.contains("<title>Tom &amp; Jerry</title>"));
+        assert!(svg.contains(">&lt;&quot;cheese&quot;&gt;</text>"));
+        let xml: XMLElement = (&doc).into();
+        assert_eq!(xml.to_string(), svg);
+        assert_eq!(xml.to_string_pretty("\n", "  "), doc.to_pretty_svg_string());
+    }
+
+    #[test]
+    fn world_transform() {
+        let world = World::new("site", Frame::new(3.0, 4.0).rotate(30.0).flip_y().scale_x(2.0));
+        let xml: XMLElement = world.clone().into();
+        let xml = xml.to_string();
+        let start = xml.find("transform=\"").unwrap() + "transform=\"".len();
+        let transform = &xml[start..start + xml[start..].find('"').unwrap()];
+        assert!(transform.starts_with("matrix("));
+        Matrix3::parse_svg(transform).unwrap().assert_approx_eq(world.matrix());
+        let p = Point::new(1.0, 2.0);
+        let back = p.transform(&world.matrix()).transform(&world.matrix().inverse());
+        assert_approx_eq!(back.x, 1.0);
+        assert_approx_eq!(back.y, 2.0);
+    }
+
+    #[test]
+    fn hit_test() {
+        let mut doc = Document::new(0.0, 0.0, 30.0, 30.0, 10);
+        let mut garden = World::new("garden", Frame::new(10.0, 0.0).rotate(90.0));
+        let mut bed = Area::new("bed", vec![(0.0, 0.0), (6.0, 0.0), (6.0, 4.0), (0.0, 4.0)])
+            .world(Point::new(1.0, 1.0), None);
+        bed.add(Tree {
+            name: "oak".into(),
+            species: "Quercus robur".into(),
+            trunk_diameter: 0.5,
+            crown_diameter: Some(2.0),
+            location: Point::new(2.0, 1.0),
+            label_location: Point::new(2.0, 1.0),
+        });
+        garden.add(bed);
+        doc.add(garden);
+        doc.add(Path::new("hedge", vec![(0.0, 20.0), (20.0, 20.0)]));
+        // the oak is at (3, 2) in the garden, rotated to (-2, 3) and moved to (8, 3)
+        assert_eq!(vec!["bed", "oak"], doc.hit_test(Point::new(8.0, 3.0)));
+        assert_eq!(vec!["bed"], doc.hit_test(Point::new(9.0, 5.0)));
+        assert!(doc.hit_test(Point::new(3.0, 2.0)).is_empty());
+        assert_eq!(vec!["hedge"], doc.hit_test(Point::new(10.0, 20.1)));
+        assert!(doc.hit_test(Point::new(10.0, 20.2)).is_empty());
+        let bed = doc.frame("bed").unwrap();
+        assert_approx_eq!(Point::new(2.0, 1.0).transform(&bed).x, 8.0);
+        assert_approx_eq!(Point::new(8.0, 3.0).transform(&bed.inverse()).y, 1.0);
+        assert!(doc.frame("hedge").is_none());
+    }
+}
@@ -2,25 +2,50 @@
 
 
 
+use std::any::Any;
+
 use simple_xml_serialize::XMLElement;
 
-use crate::coordinate::Coordinate;
+use crate::bounding_box::BoundingBox;
+use crate::point::Point;
+use crate::drawable::{self, Drawable};
+use crate::error::{self, Result};
+use crate::matrix3::Matrix3;
 
-#[derive(Debug)]
+/// SVG default font size, used when a label has no size.
+pub const DEFAULT_FONT_SIZE: f64 = 16.0;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Label {
-    pub location: Coordinate,
+    pub location: Point,
     pub text: String,
     pub size: Option<f64>,
 }
 
 impl Label {
-    pub fn new(location: Coordinate, text: &str) -> Label {
+    pub fn new(location: Point, text: &str) -> Label {
         Label {
             location,
             text: text.to_string(),
             size: None,
         }
     }
+    /// Like `new`, but rejects a non-finite location.
+    pub fn try_new(location: Point, text: &str) -> Result<Label> {
+        let label = Label::new(location, text);
+        label.validate()?;
+        Ok(label)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let element = format!("label '{}'", self.text);
+        error::check_coordinate(&element, "location", &self.location)?;
+        match self.size {
+            Some(size) => error::check_size(&element, "size", size),
+            None => Ok(()),
+        }
+    }
+
     pub fn size(mut self, size: f64) -> Self {
         self.size = Some(size);
         self
@@ -38,6 +63,45 @@ impl Into<XMLElement> for Label {
     }
 }
 
+impl Drawable for Label {
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Approximation assuming an average glyph width of 0.6 times the font
+    /// size; the glyphs are above the baseline, on the -y side of `location`.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let size = self.size.unwrap_or(DEFAULT_FONT_SIZE);
+        let half_width = self.text.chars().count() as f64 * size * 0.3;
+        Some(BoundingBox::new(self.location.x - half_width, self.location.y - size,
+                              self.location.x + half_width, self.location.y))
+    }
+
+    fn transform(&mut self, matrix: &Matrix3) {
+        self.location = self.location.transform(matrix);
+        let scale = drawable::scale_factor(matrix);
+        if self.size.is_some() || scale != 1.0 {
+            self.size = Some(self.size.unwrap_or(DEFAULT_FONT_SIZE) * scale);
+        }
+    }
+
+    fn render(&self) -> XMLElement {
+        self.clone().into()
+    }
+
+    fn box_clone(&self) -> Box<dyn Drawable> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn Drawable) -> bool {
+        other.as_any().downcast_ref::<Self>() == Some(self)
+    }
+}
+
 #[derive(Debug)]
 pub struct Title(pub String);
 
@@ -56,4 +120,29 @@ impl Into<XMLElement> for Description {
         XMLElement::new("desc")
             .text(self.0)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use crate::drawable::Drawable;
+    use crate::matrix3::Matrix3;
+    use crate::point::Point;
+    use crate::text::{DEFAULT_FONT_SIZE, Label};
+    use crate::vector2::Vector2;
+
+    #[test]
+    fn bounding_box() {
+        let b = Label::new(Point::new(10.0, 5.0), "ab").size(2.0).bounding_box().unwrap();
+        assert_eq!((8.8, 3.0, 11.2, 5.0), (b.min_x, b.min_y, b.max_x, b.max_y));
+    }
+
+    #[test]
+    fn transform() {
+        let mut label = Label::new(Point::new(1.0, 1.0), "a");
+        label.transform(&Matrix3::scale(2.0, 2.0));
+        assert_eq!(Point::new(2.0, 2.0), label.location);
+        assert_eq!(Some(2.0 * DEFAULT_FONT_SIZE), label.size);
+        let mut label = Label::new(Point::new(1.0, 1.0), "a");
+        label.transform(&Matrix3::translate(Vector2::new(1.0, 0.0)));
+        assert_eq!(None, label.size);
+    }
+}
@@ -1,26 +1,61 @@
 // (c) 2019 Joost Yervante Damad <joost@damad.be>
 
+use std::any::Any;
+
 use simple_xml_serialize::XMLElement;
 
-use crate::Circle;
+use crate::{Area, Circle};
+use crate::bounding_box::BoundingBox;
 use crate::color::Color;
-use crate::coordinate::Coordinate;
+use crate::point::Point;
+use crate::drawable::{self, Drawable};
+use crate::error::{self, Result};
+use crate::matrix3::Matrix3;
+use crate::offset::Join;
 use crate::text::{Description, Label, Title};
 
-#[derive(Debug)]
+/// Stroke width trees are drawn with.
+pub const STROKE_WIDTH: f64 = 0.2;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Tree {
     pub name: String,
     pub species: String,
     pub trunk_diameter: f64,
     pub crown_diameter: Option<f64>,
-    pub location: Coordinate,
-    pub label_location: Coordinate,
+    pub location: Point,
+    pub label_location: Point,
+}
+
+impl Tree {
+    /// Reject non-finite locations and negative or non-finite diameters.
+    pub fn validate(&self) -> Result<()> {
+        let element = format!("tree '{}'", self.name);
+        error::check_coordinate(&element, "location", &self.location)?;
+        error::check_coordinate(&element, "label location", &self.label_location)?;
+        error::check_size(&element, "trunk diameter", self.trunk_diameter)?;
+        match self.crown_diameter {
+            Some(d) => error::check_size(&element, "crown diameter", d),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether `point` is under the crown, or on the trunk for a tree without crown.
+    pub fn contains(&self, point: Point) -> bool {
+        let r = self.crown_diameter.unwrap_or(0.0).max(self.trunk_diameter) / 2.0;
+        (point.x - self.location.x).hypot(point.y - self.location.y) <= r
+    }
+
+    /// The area within `distance` of the trunk center, like a root protection zone.
+    pub fn zone(&self, distance: f64) -> Area {
+        Area::around(&self.name, self.location, distance, Join::Round).color(Color::Green)
+    }
 }
 
 impl Into<XMLElement> for Tree {
     fn into(self) -> XMLElement {
         XMLElement::new("g")
-            .attr("stroke-width", 0.2)
+            .attr("stroke-width", STROKE_WIDTH)
             .attr("id", format!("tree-{}", self.name))
             .element(Label::new(self.label_location, &self.name))
             .element(Title(format!("Tree {}", self.name)))
@@ -32,4 +67,40 @@ impl Into<XMLElement> for Tree {
             .element(Circle::new(self.location.x, self.location.y, self.trunk_diameter / 2.0,
                                  Color::Brown, Color::Maroon))
     }
+}
+
+impl Drawable for Tree {
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let r = self.crown_diameter.unwrap_or(0.0).max(self.trunk_diameter) / 2.0;
+        let tree = BoundingBox::point(self.location.x, self.location.y).grow(r);
+        Label::new(self.label_location, &self.name).bounding_box().map(|l| l.union(tree))
+    }
+
+    fn transform(&mut self, matrix: &Matrix3) {
+        let scale = drawable::scale_factor(matrix);
+        self.location = self.location.transform(matrix);
+        self.label_location = self.label_location.transform(matrix);
+        self.trunk_diameter *= scale;
+        self.crown_diameter = self.crown_diameter.map(|d| d * scale);
+    }
+
+    fn render(&self) -> XMLElement {
+        self.clone().into()
+    }
+
+    fn box_clone(&self) -> Box<dyn Drawable> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn Drawable) -> bool {
+        other.as_any().downcast_ref::<Self>() == Some(self)
+    }
 }
\ No newline at end of file
@@ -1 +1,104 @@
 // (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+/// `value` rounded to `precision` decimal places, without trailing zeros.
+pub fn format_number(value: f64, precision: usize) -> String {
+    let s = format!("{:.*}", precision, value);
+    let s = if s.contains('.') { s.trim_end_matches('0').trim_end_matches('.') } else { &s[..] };
+    match s {
+        "-0" => "0".to_string(),
+        s => s.to_string(),
+    }
+}
+
+/// `s` with `&`, `<`, `>` and quotes replaced by entities, for XML text and
+/// attribute values.
+pub fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Round every number in `s`, an SVG attribute value like path data or a
+/// list of points, to `precision` decimal places.
+pub fn round_numbers(s: &str, precision: usize) -> String {
+    let bytes = s.as_bytes();
+    let mut out = String::with_capacity(s.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let starts_number = c.is_ascii_digit()
+            || ((c == b'-' || c == b'.') && bytes.get(i + 1).is_some_and(|n| n.is_ascii_digit()));
+        if !starts_number {
+            i += 1;
+            continue;
+        }
+        out.push_str(&s[copied..i]);
+        let start = i;
+        if c == b'-' {
+            i += 1;
+        }
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i < bytes.len() && bytes[i] == b'.' {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+        if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+            let mut j = i + 1;
+            if j < bytes.len() && (bytes[j] == b'-' || bytes[j] == b'+') {
+                j += 1;
+            }
+            if j < bytes.len() && bytes[j].is_ascii_digit() {
+                while j < bytes.len() && bytes[j].is_ascii_digit() {
+                    j += 1;
+                }
+                i = j;
+            }
+        }
+        match s[start..i].parse::<f64>() {
+            Ok(v) => out.push_str(&format_number(v, precision)),
+            Err(_) => out.push_str(&s[start..i]),
+        }
+        copied = i;
+    }
+    out.push_str(&s[copied..]);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::{escape_xml, format_number, round_numbers};
+
+    #[test]
+    fn format() {
+        assert_eq!("3", format_number(3.0000000000000004, 3));
+        assert_eq!("0.125", format_number(0.125, 3));
+        assert_eq!("0", format_number(-0.0001, 2));
+        assert_eq!("12", format_number(12.0, 0));
+    }
+
+    #[test]
+    fn round() {
+        assert_eq!("M0.1,-2 L0,12 z", round_numbers("M0.1000001,-2.0000000004 L1e-7,12 z", 3));
+        assert_eq!("translate(1.5 -0.333)", round_numbers("translate(1.5 -0.33333)", 3));
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!("Tom &amp; Jerry &lt;&quot;&apos;&gt;", escape_xml("Tom & Jerry <\"'>"));
+        assert_eq!("plain", escape_xml("plain"));
+    }
+}
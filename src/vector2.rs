@@ -1,5 +1,5 @@
 use std::ops;
-use crate::coordinate::Coordinate;
+use crate::point::Point;
 
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
@@ -85,8 +85,14 @@ impl ops::Div<f64> for Vector2 {
     }
 }
 
-impl From<&Coordinate> for Vector2 {
-    fn from(c:&Coordinate) -> Vector2 {
+impl From<&Point> for Vector2 {
+    fn from(c: &Point) -> Vector2 {
+        Vector2 { x: c.x, y: c.y }
+    }
+}
+
+impl From<Point> for Vector2 {
+    fn from(c: Point) -> Vector2 {
         Vector2 { x: c.x, y: c.y }
     }
 }
@@ -0,0 +1,92 @@
+// (c) 2019 Joost Yervante Damad <joost@damad.be>
+
+//! Polling for changes of plan source files.
+
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Modification time and length, `None` when the file can not be read.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &PathBuf) -> Stamp {
+    fs::metadata(path).ok().and_then(|m| m.modified().ok().map(|t| (t, m.len())))
+}
+
+/// Detects changes of a set of files by polling their metadata.
+pub struct Watcher {
+    files: Vec<PathBuf>,
+    stamps: Vec<Stamp>,
+}
+
+impl Watcher {
+    pub fn new<P: Into<PathBuf>>(files: Vec<P>) -> Watcher {
+        let files: Vec<PathBuf> = files.into_iter().map(|f| f.into()).collect();
+        let stamps = files.iter().map(stamp).collect();
+        Watcher { files, stamps }
+    }
+
+    /// Whether any file changed, appeared or disappeared since the previous poll.
+    pub fn poll(&mut self) -> bool {
+        let stamps: Vec<Stamp> = self.files.iter().map(stamp).collect();
+        let changed = stamps != self.stamps;
+        self.stamps = stamps;
+        changed
+    }
+
+    /// Call `render` now and after every change, checking every `interval`.
+    ///
+    /// Errors of `render` are passed to `report` and watching continues;
+    /// this only returns when `report` returns `false`.
+    pub fn run<E, R, F>(&mut self, interval: Duration, mut render: R, mut report: F)
+        where R: FnMut() -> Result<(), E>, F: FnMut(Result<(), E>) -> bool {
+        if !report(render()) {
+            return;
+        }
+        loop {
+            thread::sleep(interval);
+            if self.poll() && !report(render()) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::time::Duration;
+
+    use crate::watch::Watcher;
+
+    #[test]
+    fn poll() {
+        let file = std::env::temp_dir().join(format!("jyd2d-watch-{}.toml", std::process::id()));
+        let _ = fs::remove_file(&file);
+        let mut watcher = Watcher::new(vec![file.clone()]);
+        assert!(!watcher.poll());
+        fs::write(&file, "a").unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+        fs::write(&file, "ab").unwrap();
+        assert!(watcher.poll());
+        fs::remove_file(&file).unwrap();
+        assert!(watcher.poll());
+    }
+
+    #[test]
+    fn run_reports_errors() {
+        let mut watcher = Watcher::new(Vec::<String>::new());
+        let mut reports = vec![];
+        let mut count = 0;
+        watcher.run(Duration::from_millis(1), || {
+            count += 1;
+            if count == 1 { Err("broken") } else { Ok(()) }
+        }, |r| {
+            reports.push(r);
+            false
+        });
+        assert_eq!(vec![Err("broken")], reports);
+    }
+}